@@ -0,0 +1,720 @@
+//! Procedural backend of the `bop!` macro from [libsugar](https://docs.rs/libsugar)
+//!
+//! Enabled by the `"proc-macro"` feature of libsugar.
+//! The input is checked against the `bop!` grammar so misuse is reported on the offending token,
+//! then it is handed unchanged to the declarative `_bop!`, so both backends expand to the same code.
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+use std::ops::Range;
+
+/// Checks the input of `bop!`, used as `_bop_proc!{ $crate ; ... }`
+#[proc_macro]
+pub fn bop(input: TokenStream) -> TokenStream {
+    let mut iter = input.into_iter();
+    let krate = iter.next().expect("expected `$crate ;`");
+    iter.next();
+    let body: TokenStream = iter.collect();
+    match check(&lex(body.clone())) {
+        Ok(()) => forward(krate, body),
+        Err(e) => e.into_tokens(),
+    }
+}
+
+fn forward(krate: TokenTree, body: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = vec![
+        krate,
+        Punct::new(':', Spacing::Joint).into(),
+        Punct::new(':', Spacing::Alone).into(),
+        Ident::new("_bop", Span::call_site()).into(),
+        Punct::new('!', Spacing::Alone).into(),
+        Group::new(Delimiter::Brace, body).into(),
+    ];
+    tokens.into_iter().collect()
+}
+
+struct Error {
+    span: Span,
+    msg: String,
+}
+
+impl Error {
+    fn into_tokens(self) -> TokenStream {
+        let mut bang = Punct::new('!', Spacing::Alone);
+        bang.set_span(self.span);
+        let mut msg = Literal::string(&self.msg);
+        msg.set_span(self.span);
+        let mut args = Group::new(Delimiter::Parenthesis, TokenTree::from(msg).into());
+        args.set_span(self.span);
+        let tokens: Vec<TokenTree> = vec![
+            Ident::new("compile_error", self.span).into(),
+            bang.into(),
+            args.into(),
+        ];
+        tokens.into_iter().collect()
+    }
+}
+
+fn err<T>(span: Span, msg: impl Into<String>) -> Result<T, Error> {
+    Err(Error {
+        span,
+        msg: msg.into(),
+    })
+}
+
+enum Kind {
+    Punct(String),
+    Ident(String),
+    Lifetime,
+    Literal,
+    Group(Delimiter),
+}
+
+/// A token as `macro_rules!` sees it, multi character operators are joined
+struct Tok {
+    kind: Kind,
+    span: Span,
+}
+
+impl Tok {
+    fn is(&self, op: &str) -> bool {
+        matches!(&self.kind, Kind::Punct(p) if p == op)
+    }
+
+    fn is_ident(&self, name: &str) -> bool {
+        matches!(&self.kind, Kind::Ident(i) if i == name)
+    }
+
+    fn is_block(&self) -> bool {
+        matches!(self.kind, Kind::Group(Delimiter::Brace))
+    }
+
+    /// Whether an `expr` fragment may start here, otherwise `macro_rules!` moves on to the next arm
+    fn begins_expr(&self) -> bool {
+        match &self.kind {
+            Kind::Punct(p) => matches!(
+                p.as_str(),
+                "-" | "!" | "*" | "&" | "&&" | "|" | "||" | ".." | "..=" | "<" | "::" | "#"
+            ),
+            _ => true,
+        }
+    }
+
+    fn text(&self) -> &str {
+        match &self.kind {
+            Kind::Punct(s) | Kind::Ident(s) => s,
+            _ => "",
+        }
+    }
+}
+
+const OPS: &[&str] = &[
+    "<<=", ">>=", "...", "..=", "&&", "||", "==", "!=", "<=", ">=", "=>", "->", "::", "..", "+=",
+    "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<", ">>",
+];
+
+const CMP: &[&str] = &["<", "<=", ">", ">=", "==", "!="];
+
+const COMPOUND: &[&str] = &["+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<=", ">>="];
+
+const FOLD: &[&str] = &[
+    "min",
+    "max",
+    "partial_min",
+    "partial_max",
+    "nan_min",
+    "nan_max",
+];
+
+fn lex(input: TokenStream) -> Vec<Tok> {
+    let trees: Vec<TokenTree> = input.into_iter().collect();
+    let mut toks = Vec::with_capacity(trees.len());
+    let mut i = 0;
+    while i < trees.len() {
+        match &trees[i] {
+            TokenTree::Punct(p) if p.as_char() == '\'' => {
+                toks.push(Tok {
+                    kind: Kind::Lifetime,
+                    span: p.span(),
+                });
+                i += 2;
+            }
+            TokenTree::Punct(_) => {
+                let mut run = Vec::new();
+                while let Some(TokenTree::Punct(p)) = trees.get(i) {
+                    run.push(p);
+                    i += 1;
+                    if p.spacing() == Spacing::Alone {
+                        break;
+                    }
+                }
+                let chars: String = run.iter().map(|p| p.as_char()).collect();
+                let mut j = 0;
+                while j < run.len() {
+                    let rest = &chars[j..];
+                    let len = OPS
+                        .iter()
+                        .find(|op| rest.starts_with(*op))
+                        .map_or(1, |op| op.len());
+                    toks.push(Tok {
+                        kind: Kind::Punct(rest[..len].to_string()),
+                        span: run[j].span(),
+                    });
+                    j += len;
+                }
+            }
+            TokenTree::Ident(id) => {
+                toks.push(Tok {
+                    kind: Kind::Ident(id.to_string()),
+                    span: id.span(),
+                });
+                i += 1;
+            }
+            TokenTree::Literal(l) => {
+                toks.push(Tok {
+                    kind: Kind::Literal,
+                    span: l.span(),
+                });
+                i += 1;
+            }
+            TokenTree::Group(g) => {
+                toks.push(Tok {
+                    kind: Kind::Group(g.delimiter()),
+                    span: g.span(),
+                });
+                i += 1;
+            }
+        }
+    }
+    toks
+}
+
+/// Span of `ts[i]`, or of the token before it when `i` is the end
+fn near(ts: &[Tok], i: usize, fallback: Span) -> Span {
+    ts.get(i)
+        .or_else(|| i.checked_sub(1).and_then(|i| ts.get(i)))
+        .map_or(fallback, |t| t.span)
+}
+
+/// Index of the first top level `stop` from `i`, skipping closure parameters and turbofish arguments
+fn scan(ts: &[Tok], mut i: usize, stop: &str) -> usize {
+    let start = i;
+    let mut angle = 0usize;
+    while i < ts.len() {
+        let t = &ts[i];
+        if angle > 0 {
+            if t.is("<") {
+                angle += 1
+            } else if t.is(">") {
+                angle -= 1
+            } else if t.is(">>") {
+                angle = angle.saturating_sub(2)
+            }
+        } else if t.is("<") && i > start && ts[i - 1].is("::") {
+            angle = 1
+        } else if t.is("|")
+            && (i == start
+                || ts[i - 1].is_ident("move")
+                || matches!(ts[i - 1].kind, Kind::Punct(_)))
+        {
+            i += 1;
+            while i < ts.len() && !ts[i].is("|") {
+                i += 1
+            }
+        } else if t.is(stop) {
+            return i;
+        }
+        i += 1;
+    }
+    ts.len()
+}
+
+fn split(ts: &[Tok], sep: &str) -> Vec<Range<usize>> {
+    let mut parts = Vec::new();
+    let mut i = 0;
+    loop {
+        let j = scan(ts, i, sep);
+        parts.push(i..j);
+        if j == ts.len() {
+            return parts;
+        }
+        i = j + 1;
+    }
+}
+
+fn check(ts: &[Tok]) -> Result<(), Error> {
+    let first = match ts.first() {
+        Some(t) => t,
+        None => return Ok(()),
+    };
+    if first.is_ident("let") {
+        return check_let(first, &ts[1..]);
+    }
+    if let Some((m, needs_else)) = chain(ts) {
+        return check_chain(ts, m, needs_else);
+    }
+    if matches!(first.kind, Kind::Ident(_)) && ts.len() > 1 && ts[1].is(";") {
+        if ts.len() == 2 {
+            return Ok(());
+        }
+        if scan(ts, 2, "=>") < ts.len() {
+            return check_in(ts, 2);
+        }
+    }
+    if ts.len() > 1 {
+        if first.is_ident("matches") && (ts[1].is("||") || ts[1].is("&&")) {
+            return check_matches(ts);
+        }
+        if FOLD.iter().any(|f| first.is_ident(f)) && ts[1].begins_expr() {
+            return check_operands(first, &ts[1..], 1);
+        }
+        if first.is_ident("fold") && ts[1].begins_expr() {
+            return check_fold(ts);
+        }
+        if first.is_ident("cmp") {
+            return check_cmp(ts);
+        }
+    }
+    if first.is("+") {
+        return check_operands(first, &ts[1..], 2);
+    }
+    if first.is("*") {
+        let parts = split(&ts[1..], ",");
+        if parts.iter().filter(|r| !r.is_empty()).count() >= 2 {
+            return check_operands(first, &ts[1..], 2);
+        }
+        return Ok(());
+    }
+    if first.is("||") || first.is("&&") || first.is("=") {
+        return check_batch(ts);
+    }
+    if COMPOUND.iter().any(|op| first.is(op)) {
+        return check_compound(ts);
+    }
+    if scan(ts, 0, "=>") < ts.len() {
+        return check_in(ts, 0);
+    }
+    Ok(())
+}
+
+fn check_let(kw: &Tok, ts: &[Tok]) -> Result<(), Error> {
+    if ts.is_empty() {
+        return err(kw.span, "expected a binding after `let`");
+    }
+    let n = ts.len();
+    let has_else =
+        n >= 3 && ts[n - 2].is_ident("else") && ts[n - 1].is_block() && !ts[n - 3].is_block();
+    let links = if has_else { &ts[..n - 2] } else { ts };
+    let mut i = 0;
+    loop {
+        let start = i;
+        while i < links.len() && !(links[i].is("|") || links[i].is("=") || links[i].is(",")) {
+            i += 1;
+        }
+        if i == start {
+            return err(near(links, i, kw.span), "expected a pattern");
+        }
+        if links.get(i).map_or(false, |t| t.is("|")) {
+            let bar = i;
+            i += 1;
+            let mut angle = 0usize;
+            while i < links.len() {
+                let t = &links[i];
+                if angle == 0 && (t.is("=") || t.is(",")) {
+                    break;
+                }
+                if t.is("<") {
+                    angle += 1
+                } else if t.is(">") {
+                    angle = angle.saturating_sub(1)
+                } else if t.is(">>") {
+                    angle = angle.saturating_sub(2)
+                }
+                i += 1;
+            }
+            if i == bar + 1 {
+                return err(links[bar].span, "expected a type after `|`");
+            }
+        }
+        if links.get(i).map_or(false, |t| t.is("=")) {
+            let eq = i;
+            i = scan(links, eq + 1, ",");
+            if i == eq + 1 {
+                return err(links[eq].span, "expected an expression after `=`");
+            }
+        } else if has_else {
+            return err(
+                links[start].span,
+                "every binding of a `let ... else` needs `= expr`",
+            );
+        }
+        match links.get(i) {
+            None => return Ok(()),
+            Some(comma) => {
+                i += 1;
+                if i == links.len() {
+                    return err(comma.span, "expected a binding after `,`");
+                }
+            }
+        }
+    }
+}
+
+/// Index of `match` and whether an `else` is required, if `ts` is a `match &&` or `match ||` chain
+fn chain(ts: &[Tok]) -> Option<(usize, bool)> {
+    let mut i = 0;
+    let mut needs_else = true;
+    let mut labeled = true;
+    if ts[0].is_ident("bool") {
+        needs_else = false;
+        i = 1;
+    } else if ts[0].is("!") && ts.get(1).map_or(false, |t| t.is_ident("loop")) {
+        labeled = false;
+        i = 2;
+        if ts.get(2).map_or(false, |t| t.is_ident("bool")) {
+            needs_else = false;
+            i = 3;
+        }
+    }
+    let flavoured = i > 0;
+    if labeled
+        && matches!(ts.get(i).map(|t| &t.kind), Some(Kind::Lifetime))
+        && ts.get(i + 1).map_or(false, |t| t.is(":"))
+    {
+        i += 2;
+    }
+    if !ts.get(i).map_or(false, |t| t.is_ident("match")) {
+        return None;
+    }
+    if flavoured || ts.get(i + 1).map_or(false, |t| t.is("&&") || t.is("||")) {
+        Some((i, needs_else))
+    } else {
+        None
+    }
+}
+
+fn check_chain(ts: &[Tok], m: usize, needs_else: bool) -> Result<(), Error> {
+    let op = match ts.get(m + 1) {
+        Some(t) if t.is("&&") || t.is("||") => t,
+        _ => {
+            return err(
+                near(ts, m + 1, ts[m].span),
+                "expected `&&` or `||` after `match`",
+            )
+        }
+    };
+    let and = op.is("&&");
+    let rest = &ts[m + 2..];
+    let arrow = match rest.iter().position(|t| t.is("=>")) {
+        Some(a) => a,
+        None => return err(op.span, "expected `=> { ... }` after the chain"),
+    };
+    let links = &rest[..arrow];
+    let parts = split(links, ",");
+    let single = parts.len() == 1;
+    for r in parts {
+        if r.is_empty() {
+            if and || single {
+                continue;
+            }
+            return err(near(links, r.start, op.span), "expected `pattern = expr`");
+        }
+        check_link(&links[r], and)?;
+    }
+    let tail = &rest[arrow + 1..];
+    match tail.first() {
+        Some(t) if t.is_block() => {}
+        _ => {
+            return err(
+                near(rest, arrow + 1, op.span),
+                "expected a block after `=>`",
+            )
+        }
+    }
+    match &tail[1..] {
+        [] if needs_else => err(
+            tail[0].span,
+            "expected `else`, this chain needs an `else` block unless it is a `bool` chain",
+        ),
+        [] => Ok(()),
+        [e, b] if e.is_ident("else") && b.is_block() => Ok(()),
+        [e, bar, p @ .., bar2, b]
+            if and
+                && e.is_ident("else")
+                && bar.is("|")
+                && !p.is_empty()
+                && bar2.is("|")
+                && b.is_block() =>
+        {
+            Ok(())
+        }
+        [e, ..] if e.is_ident("else") && and => err(
+            e.span,
+            "expected `{ ... }` or `|fail| { ... }` after `else`",
+        ),
+        [e, ..] if e.is_ident("else") => err(e.span, "expected a block after `else`"),
+        [t, ..] => err(t.span, "expected `else` after the block"),
+    }
+}
+
+fn check_link(link: &[Tok], and: bool) -> Result<(), Error> {
+    let mut l = link;
+    if let [Tok {
+        kind: Kind::Literal,
+        ..
+    }, colon, ..] = l
+    {
+        if colon.is(":") {
+            if !and {
+                return err(l[0].span, "labels are only supported in `match &&` chains");
+            }
+            l = &l[2..];
+            if l.is_empty() {
+                return err(colon.span, "expected a link after the label");
+            }
+        }
+    }
+    if l[0].is_ident("if") {
+        if !and {
+            return err(
+                l[0].span,
+                "`if` links are only supported in `match &&` chains",
+            );
+        }
+        if l.len() == 1 {
+            return err(l[0].span, "expected a condition after `if`");
+        }
+        return Ok(());
+    }
+    match l.iter().position(|t| t.is("=")) {
+        None if and => err(l[0].span, "expected `pattern = expr` or `if cond`"),
+        None => err(l[0].span, "expected `pattern = expr`"),
+        Some(0) => err(l[0].span, "expected a pattern before `=`"),
+        Some(e) if e + 1 == l.len() => err(l[e].span, "expected an expression after `=`"),
+        _ => Ok(()),
+    }
+}
+
+fn check_matches(ts: &[Tok]) -> Result<(), Error> {
+    let body = &ts[2..];
+    let semi = scan(body, 0, ";");
+    if semi == body.len() {
+        return err(ts[1].span, "expected `values ; patterns`");
+    }
+    let values = split(&body[..semi], ",");
+    for r in &values {
+        if r.is_empty() {
+            return err(near(body, r.start, ts[1].span), "expected a value");
+        }
+    }
+    let pats = &body[semi + 1..];
+    let parts = split(pats, ",");
+    if values.len() > 1 && parts.len() > 1 {
+        return err(
+            pats[parts[0].end].span,
+            "several values can only be matched against a single pattern",
+        );
+    }
+    let last = parts.len() - 1;
+    for (n, r) in parts.into_iter().enumerate() {
+        if r.is_empty() && !(n == last && n > 0) {
+            return err(near(pats, r.start, body[semi].span), "expected a pattern");
+        }
+    }
+    Ok(())
+}
+
+fn check_operands(kw: &Tok, ts: &[Tok], min: usize) -> Result<(), Error> {
+    let parts = split(ts, ",");
+    let last = parts.len() - 1;
+    let mut count = 0;
+    for (n, r) in parts.into_iter().enumerate() {
+        if r.is_empty() {
+            if n == last && n > 0 {
+                continue;
+            }
+            return err(near(ts, r.start, kw.span), "expected an operand");
+        }
+        count += 1;
+    }
+    if count < min {
+        return err(
+            kw.span,
+            format!("`{}` needs at least {} operands", kw.text(), min),
+        );
+    }
+    Ok(())
+}
+
+fn check_fold(ts: &[Tok]) -> Result<(), Error> {
+    let body = &ts[1..];
+    let semi = scan(body, 0, ";");
+    if semi == body.len() {
+        return err(ts[0].span, "expected `;` after the fold function");
+    }
+    if semi == 0 {
+        return err(body[0].span, "expected a fold function before `;`");
+    }
+    check_operands(&body[semi], &body[semi + 1..], 1)
+}
+
+fn check_cmp(ts: &[Tok]) -> Result<(), Error> {
+    let body = &ts[1..];
+    let mut start = 0;
+    for (i, t) in body.iter().enumerate() {
+        if CMP.iter().any(|op| t.is(op)) {
+            if i == start {
+                return err(t.span, format!("expected an operand before `{}`", t.text()));
+            }
+            start = i + 1;
+        }
+    }
+    if start == 0 {
+        return err(
+            ts[0].span,
+            "expected a comparison operator like `<` in the chain",
+        );
+    }
+    if start == body.len() {
+        let op = &body[start - 1];
+        return err(
+            op.span,
+            format!("expected an operand after `{}`", op.text()),
+        );
+    }
+    Ok(())
+}
+
+fn check_batch(ts: &[Tok]) -> Result<(), Error> {
+    let body = &ts[1..];
+    if body.is_empty() {
+        return err(
+            ts[0].span,
+            format!("expected an expression after `{}`", ts[0].text()),
+        );
+    }
+    let semi = scan(body, 0, ";");
+    if semi == body.len() {
+        return Ok(());
+    }
+    if semi == 0 {
+        return err(body[0].span, "expected an expression before `;`");
+    }
+    let items = &body[semi + 1..];
+    if items.len() <= 1 {
+        return Ok(());
+    }
+    if items[1].is(";") {
+        let values = &items[2..];
+        if values.is_empty() {
+            return Ok(());
+        }
+        for r in split(values, ",") {
+            check_item(values, r, false, items[1].span)?;
+        }
+    } else {
+        for r in split(items, ",") {
+            check_item(items, r, true, body[semi].span)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_item(ts: &[Tok], r: Range<usize>, with_op: bool, fallback: Span) -> Result<(), Error> {
+    if r.is_empty() {
+        return err(near(ts, r.start, fallback), "expected an operand");
+    }
+    let mut item = &ts[r];
+    if with_op {
+        if item.len() == 1 {
+            return err(
+                item[0].span,
+                format!("expected an operand after `{}`", item[0].text()),
+            );
+        }
+        item = &item[1..];
+    }
+    let semi = scan(item, 0, ";");
+    if semi == 0 {
+        return err(item[0].span, "expected an operand before `;`");
+    }
+    if semi < item.len() {
+        match &item[semi + 1..] {
+            [t] if t.is("!") => {}
+            _ => return err(item[semi].span, "expected `;!` to swap the operands"),
+        }
+    }
+    Ok(())
+}
+
+fn check_compound(ts: &[Tok]) -> Result<(), Error> {
+    let body = &ts[1..];
+    let semi = scan(body, 0, ";");
+    if semi == 0 {
+        return err(
+            ts[0].span,
+            format!("expected a place after `{}`", ts[0].text()),
+        );
+    }
+    if semi == body.len() {
+        return err(ts[0].span, "expected `place ; values`");
+    }
+    check_operands(&ts[0], &body[semi + 1..], 1)
+}
+
+fn check_in(ts: &[Tok], start: usize) -> Result<(), Error> {
+    let body = &ts[start..];
+    let arrow = scan(body, 0, "=>");
+    let values = split(&body[..arrow], ",");
+    let count = if values.len() == 1 && values[0].is_empty() {
+        0
+    } else {
+        values.len()
+    };
+    if count > 0 {
+        for r in values {
+            if r.is_empty() {
+                return err(near(body, r.start, body[arrow].span), "expected a value");
+            }
+        }
+    }
+    let rhs = &body[arrow + 1..];
+    let skip = match rhs {
+        [a, b, ..] if a.is_ident("in") && (b.is("&&") || b.is("||")) => 2,
+        [n, a, b, ..] if n.is_ident("not") && a.is_ident("in") && (b.is("&&") || b.is("||")) => 3,
+        [m, a, ..] if (m.is_ident("none") || m.is_ident("count")) && a.is_ident("in") => 2,
+        [m, _, a, ..] if m.is_ident("at_least") && a.is_ident("in") => 3,
+        _ => {
+            return err(
+                near(rhs, 0, body[arrow].span),
+                "expected one of `in &&`, `in ||`, `not in &&`, `not in ||`, `none in`, `count in` or `at_least n in`",
+            )
+        }
+    };
+    let targets = &rhs[skip..];
+    if targets.is_empty() {
+        if count == 1 {
+            return Ok(());
+        }
+        return err(rhs[skip - 1].span, "expected a container");
+    }
+    let parts = split(targets, ",");
+    for r in &parts {
+        if r.is_empty() {
+            return err(
+                near(targets, r.start, rhs[skip - 1].span),
+                "expected a container",
+            );
+        }
+    }
+    if parts.len() > 1 && count != 1 {
+        let comma = targets[parts[0].end].span;
+        if count == 0 {
+            return err(comma, "expected a value to test against several containers");
+        }
+        return err(
+            comma,
+            "several values can only be tested against a single container",
+        );
+    }
+    Ok(())
+}
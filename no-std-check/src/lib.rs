@@ -0,0 +1,69 @@
+//! Expands the alloc based collection macros in a `no_std` crate, built by CI
+#![no_std]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::vec::Vec;
+use libsugar::*;
+
+pub fn collections(flag: bool) -> (BTreeMap<u8, u8>, BTreeSet<u8>, Box<[u8]>) {
+    let mut m = btmap! { 1 => 2, 7 => 8 if flag, 9 =>? Some(10), ..[(3, 4)] };
+    map_append! { m; 5 => 6 };
+    let s = btset![1, 2 if flag, ..[3]];
+    let a = arr![1, ..[2, 3]];
+    (m, s, a)
+}
+
+pub fn sequences() -> (VecDeque<u8>, LinkedList<u8>, BinaryHeap<u8>) {
+    (deque![1, 2], list![1, 2], heap![3, 1, 2])
+}
+
+pub fn min_heap() -> BinaryHeap<core::cmp::Reverse<u8>> {
+    heap![min; 3, 1, ..[2]]
+}
+
+pub fn boxed() -> Box<u8> {
+    new!(1u8)
+}
+
+pub fn nested() -> BTreeMap<&'static str, BTreeSet<u8>> {
+    lit!(@btmap { "a": @btset #{1, 2}, "b": @btset #{3} })
+}
+
+pub fn comprehensions() -> (Vec<u8>, BTreeMap<u8, u8>) {
+    let v = vec_comp![x * 2; for x in 0..3u8; if x > 0];
+    let m = map_comp! { let BTreeMap::new(); x => x + 1; for x in 0..3u8 };
+    (v, m)
+}
+
+pub fn arrays() -> ([u8; 3], [u8; 4], Box<[u8]>) {
+    (arr![fn |i| i as u8; 3], arr![in 0..; 4], arr![0; 2])
+}
+
+pub fn grouped() -> BTreeMap<u8, Vec<u8>> {
+    let mut m = multimap! { let BTreeMap::new(); Vec; 1 => 2, 1 => 3 };
+    map_append! { multi m; 2 => 4 };
+    m
+}
+
+pub fn merged(mut m: BTreeMap<u8, u8>) -> Vec<(u8, u8)> {
+    map_append! { m; merge |a, b| a + b; 1 => 1 };
+    map_append! { m; keep; 2 => 2 };
+    map_append! { m; replaced; 1 => 0 }
+}
+
+pub fn algebra() -> BTreeSet<u8> {
+    set_ops!(btset![1, 2, 3] - btset![2] | btset![4])
+}
+
+pub fn shared() -> (alloc::rc::Rc<u8>, core::pin::Pin<alloc::sync::Arc<u8>>) {
+    (new!(rc 1), new!(pin_arc 2))
+}
+
+pub fn generic(flag: bool) -> LinkedList<u8> {
+    let mut l = collect![LinkedList<_>; 1, 2 if flag, ..[3]];
+    extend!(l; 4, ..[5]);
+    l
+}
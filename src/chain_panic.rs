@@ -5,7 +5,7 @@ pub trait Panic: Sized + Sync + Send + 'static {
     #[inline]
     /// Panic Self
     fn panic(self) -> ! {
-        panic!(self)
+        std::panic::panic_any(self)
     }
 }
 impl<T: Sync + Send + 'static> Panic for T {}
@@ -12,7 +12,7 @@
 /// `A.after(B) -> (B, A)`
 pub trait After<T, Output> {
     /// Make A Tuple  
-/// `A.after(B) -> (B, A)`
+    /// `A.after(B) -> (B, A)`
     fn after(self, v: T) -> Output;
 }
 
@@ -45,5 +45,4 @@ mod tests {
         let a: (i32, u8) = 1.with(2u8);
         assert_eq!(a, (1, 2));
     }
-
 }
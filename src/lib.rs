@@ -1,5 +1,5 @@
 //! libsugar provides syntactic sugar in the form of a library
-//! 
+//!
 //! ## Features
 //!
 //! default = `["std", "combin", "named-into", "macro-lit", "side-effect", "re-exports", "chain_panic", "chain_todo", "tuples", "once_get", "chain_drop"]`  
@@ -8,27 +8,213 @@
 //! - `"side-effect"` Enable mod [side_effect](side_effect/index.html)  
 //! - `"named-into"` Enable mod [named_into](named_into/index.html)  
 //! - `"combin"` Enable mod [combin](combin/index.html)  
-//! - `"macro-lit"` Enable macro like [new](macro.new.html), [list](macro.list.html)  
+//! - `"macro-lit"` Enable macro like [new](macro.new.html), [list](macro.list.html), works without `"std"` except for the hash based ones like [map](macro.map.html) and the `Mutex`/`RwLock` forms of [new](macro.new.html)  
 //! - `"chain_panic"` Enable mod [chain_panic](chain_panic/index.html)
 //! - `"chain_todo"` Enable mod [chain_todo](chain_todo/index.html)
 //! - `"chain_drop"` Enable mod [chain_drop](chain_drop/index.html)
 //! - `"tuples"` Enable mod [tuples](tuples/index.html)  
 //! - `"once_get"` Enable mod [once_get](once_get/index.html)  
 //! - `"re-exports"` Enable re-export of all mods  
+//! - `"proc-macro"` Parse [bop](macro.bop.html) with a procedural macro for span accurate errors, the expansion is unchanged  
+//! - `"new-uninit"` Enable the `uninit` form of [new](macro.new.html), needs Rust 1.82  
+//! - `"new-zeroed"` Enable the `zeroed` form of [new](macro.new.html) and implies `"new-uninit"`, needs Rust 1.92  
 //!
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 #[doc(hidden)]
-#[macro_export]
+#[macro_export(local_inner_macros)]
 macro_rules! _matchand {
-    { ; $b:block $($el:block)? } => { $b };
+    { $b:block $($el:block)? ; } => { $b };
+    { $b:block $($el:block)? ; (let $p:pat = $e:expr ; $lab:expr) $($rest:tt)* } => {
+        if let $p = $e { _matchand!($b $($el)? ; $($rest)*) } $(else $el)?
+    };
+    { $b:block $($el:block)? ; (if $c:expr ; $lab:expr) $($rest:tt)* } => {
+        if $c { _matchand!($b $($el)? ; $($rest)*) } $(else $el)?
+    };
+
+    { @fail ($($i:tt)*) $b:block [$f:pat => $el:block] ; } => { $b };
+    { @fail ($($i:tt)*) $b:block [$f:pat => $el:block] ; (let $p:pat = $e:expr ; $lab:expr) $($rest:tt)* } => {
+        if let $p = $e {
+            _matchand!(@fail ($($i)* + 1) $b [$f => $el] ; $($rest)*)
+        } else {
+            let $f = ($($i)*, $lab);
+            $el
+        }
+    };
+    { @fail ($($i:tt)*) $b:block [$f:pat => $el:block] ; (if $c:expr ; $lab:expr) $($rest:tt)* } => {
+        if $c {
+            _matchand!(@fail ($($i)* + 1) $b [$f => $el] ; $($rest)*)
+        } else {
+            let $f = ($($i)*, $lab);
+            $el
+        }
+    };
+}
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! _letchain {
+    { (loop $($l:lifetime)?) [$($link:tt)*] => $b:block else |$f:pat| $el:block } => {
+        $($l :)? loop {
+            let fail;
+            _matchand!(@fail (0usize) { break $($l)? ($b) ; } [f => { fail = f; }] ; $($link)*);
+            let $f = fail;
+            break $($l)? ($el);
+        }
+    };
+    { (bool $($l:lifetime)?) [$($link:tt)*] => $b:block else |$f:pat| $el:block } => {
+        $($l :)? loop {
+            let fail;
+            _matchand!(@fail (0usize) { $b ; break $($l)? true; } [f => { fail = f; }] ; $($link)*);
+            let $f = fail;
+            $el;
+            break $($l)? false;
+        }
+    };
+    { (!loop) [$($link:tt)*] => $b:block else |$f:pat| $el:block } => {
+        _matchand!(@fail (0usize) { $b } [$f => $el] ; $($link)*)
+    };
+    { (!loop bool) [$($link:tt)*] => $b:block else |$f:pat| $el:block } => {
+        _matchand!(@fail (0usize) { $b ; true } [$f => { $el ; false }] ; $($link)*)
+    };
+
+    { (loop $($l:lifetime)?) [$($link:tt)*] => $b:block else $el:block } => {
+        $($l :)? loop { _matchand!({ break $($l)? ($b) ; } ; $($link)*) ; break $($l)? ($el); }
+    };
+    { (bool $($l:lifetime)?) [$($link:tt)*] => $b:block $(else $el:block)? } => {
+        $($l :)? loop { _matchand!({ $b ; break $($l)? true; } ; $($link)*) ; $($el ;)? break $($l)? false; }
+    };
+    { (!loop) [$($link:tt)*] => $b:block else $el:block } => {
+        _matchand!({ $b } { $el } ; $($link)*)
+    };
+    { (!loop bool) [$($link:tt)*] => $b:block $(else $el:block)? } => {
+        _matchand!({ $b ; true } { $($el ;)? false } ; $($link)*)
+    };
+
+    { $m:tt [$($link:tt)*] , $($rest:tt)* } => { _letchain!{ $m [$($link)*] $($rest)* } };
+    { $m:tt [$($link:tt)*] if $c:expr , $($rest:tt)* } => {
+        _letchain!{ $m [$($link)* (if $c ; ::core::stringify!($c))] $($rest)* }
+    };
+    { $m:tt [$($link:tt)*] if $c:expr => $($rest:tt)* } => {
+        _letchain!{ $m [$($link)* (if $c ; ::core::stringify!($c))] => $($rest)* }
+    };
+    { $m:tt [$($link:tt)*] $lab:literal : if $c:expr , $($rest:tt)* } => {
+        _letchain!{ $m [$($link)* (if $c ; $lab)] $($rest)* }
+    };
+    { $m:tt [$($link:tt)*] $lab:literal : if $c:expr => $($rest:tt)* } => {
+        _letchain!{ $m [$($link)* (if $c ; $lab)] => $($rest)* }
+    };
+    { $m:tt [$($link:tt)*] $lab:literal : $p:pat = $e:expr , $($rest:tt)* } => {
+        _letchain!{ $m [$($link)* (let $p = $e ; $lab)] $($rest)* }
+    };
+    { $m:tt [$($link:tt)*] $lab:literal : $p:pat = $e:expr => $($rest:tt)* } => {
+        _letchain!{ $m [$($link)* (let $p = $e ; $lab)] => $($rest)* }
+    };
+    { $m:tt [$($link:tt)*] $p:pat = $e:expr , $($rest:tt)* } => {
+        _letchain!{ $m [$($link)* (let $p = $e ; ::core::stringify!($p))] $($rest)* }
+    };
+    { $m:tt [$($link:tt)*] $p:pat = $e:expr => $($rest:tt)* } => {
+        _letchain!{ $m [$($link)* (let $p = $e ; ::core::stringify!($p))] => $($rest)* }
+    };
+}
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! _matchor {
+    { ; $b:block } => { };
+    { ; $b:block $el:block } => { $el };
     { $p:pat = $e:expr; { } $($pp:pat = $ee:expr; { })+ ; $b:block $($el:block)?} => {
-        if let $p = $e { _matchand!($($pp = $ee ; {})* ; $b $($el)?) } $(else $el)?
+        if let $p = $e $b else { _matchor!($($pp = $ee ; {})* ; $b $($el)?) }
     };
     { $p:pat = $e:expr; { } ; $b:block $($el:block)? } => { if let $p = $e $b $(else $el)? };
 }
 #[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! _letelse {
+    { @ $el:block [$p:pat $(| $t:ty)? = $($e:tt)+] } => { let $p $(: $t)? = ($($e)+) else $el; };
+
+    { [$($link:tt)*] $p:pat $(| $t:ty)? = $e:expr , $($rest:tt)* } => { _letelse!{ [$($link)* [$p $(| $t)? = $e]] $($rest)* } };
+    { [$($link:tt)*] $p:pat $(| $t:ty)? = $($rest:tt)+ } => { _letelse!{ @last [$($link)*] [$p $(| $t)? =] $($rest)+ } };
+
+    { @last [$($link:tt)*] [$($cur:tt)+] else $el:block } => {
+        $( _letelse!{ @ $el $link } )*
+        _letelse!{ @ $el [$($cur)+] }
+    };
+    { @last [$($link:tt)*] [$($cur:tt)+] $t:tt $($rest:tt)* } => { _letelse!{ @last [$($link)*] [$($cur)+ $t] $($rest)* } };
+}
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! _compound {
+    { $op:tt ($x:expr) [$($v:ident)*] } => { { let x = &mut $x; $(*x $op $v;)* } };
+    { $op:tt ($x:expr) [$($v:ident)*] $a:expr $(, $($rest:tt)*)? } => { { let v = $a; _compound!($op ($x) [$($v)* v] $($($rest)*)?) } };
+}
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! _setop {
+    { @var $x:ident [$(($op:tt $v:ident $($n:tt)?))*] } => { { $(#[allow(clippy::assign_op_pattern)] { $x = _select_op!($x; $op $v $(;$n)?); })* } };
+    { @var $x:ident [$($d:tt)*] $op:tt $a:expr $(;$n:tt)? $(, $($rest:tt)*)? } => { { let v = $a; _setop!(@var $x [$($d)* ($op v $($n)?)] $($($rest)*)?) } };
+    { ($x:expr) [$(($op:tt $v:ident $($n:tt)?))*] } => { { let x = &mut $x; $(#[allow(clippy::assign_op_pattern)] { *x = _select_op!(*x; $op $v $(;$n)?); })* } };
+    { ($x:expr) [$($d:tt)*] $op:tt $a:expr $(;$n:tt)? $(, $($rest:tt)*)? } => { { let v = $a; _setop!(($x) [$($d)* ($op v $($n)?)] $($($rest)*)?) } };
+}
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! _matchop {
+    { $j:tt [$($p:tt)+] $x:expr } => { ::core::matches!($x, $($p)+) };
+    { || [$($p:tt)+] $x:expr, $($rest:expr),+ } => { ::core::matches!($x, $($p)+) || _matchop!(|| [$($p)+] $($rest),+) };
+    { && [$($p:tt)+] $x:expr, $($rest:expr),+ } => { ::core::matches!($x, $($p)+) && _matchop!(&& [$($p)+] $($rest),+) };
+}
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! _cmpchain {
+    { [$($a:tt)+] < $($rest:tt)* } => { { let a = &($($a)+); _cmpchain!(@ a < [] $($rest)*) } };
+    { [$($a:tt)+] <= $($rest:tt)* } => { { let a = &($($a)+); _cmpchain!(@ a <= [] $($rest)*) } };
+    { [$($a:tt)+] > $($rest:tt)* } => { { let a = &($($a)+); _cmpchain!(@ a > [] $($rest)*) } };
+    { [$($a:tt)+] >= $($rest:tt)* } => { { let a = &($($a)+); _cmpchain!(@ a >= [] $($rest)*) } };
+    { [$($a:tt)+] == $($rest:tt)* } => { { let a = &($($a)+); _cmpchain!(@ a == [] $($rest)*) } };
+    { [$($a:tt)+] != $($rest:tt)* } => { { let a = &($($a)+); _cmpchain!(@ a != [] $($rest)*) } };
+    { [$($a:tt)*] $t:tt $($rest:tt)* } => { _cmpchain!([$($a)* $t] $($rest)*) };
+
+    { @ $l:ident $op:tt [$($b:tt)+] < $($rest:tt)* } => { { let b = &($($b)+); *$l $op *b && _cmpchain!(@ b < [] $($rest)*) } };
+    { @ $l:ident $op:tt [$($b:tt)+] <= $($rest:tt)* } => { { let b = &($($b)+); *$l $op *b && _cmpchain!(@ b <= [] $($rest)*) } };
+    { @ $l:ident $op:tt [$($b:tt)+] > $($rest:tt)* } => { { let b = &($($b)+); *$l $op *b && _cmpchain!(@ b > [] $($rest)*) } };
+    { @ $l:ident $op:tt [$($b:tt)+] >= $($rest:tt)* } => { { let b = &($($b)+); *$l $op *b && _cmpchain!(@ b >= [] $($rest)*) } };
+    { @ $l:ident $op:tt [$($b:tt)+] == $($rest:tt)* } => { { let b = &($($b)+); *$l $op *b && _cmpchain!(@ b == [] $($rest)*) } };
+    { @ $l:ident $op:tt [$($b:tt)+] != $($rest:tt)* } => { { let b = &($($b)+); *$l $op *b && _cmpchain!(@ b != [] $($rest)*) } };
+    { @ $l:ident $op:tt [$($b:tt)+] } => { *$l $op ($($b)+) };
+    { @ $l:ident $op:tt [$($b:tt)*] $t:tt $($rest:tt)* } => { _cmpchain!(@ $l $op [$($b)* $t] $($rest)*) };
+}
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! _inop {
+    { $f:ident ; [$($v:expr),*] in && $t:expr } => { _inop!(@ $f (and) {$t} $([$t] [$v])*) };
+    { $f:ident ; [$v:expr] in && $($t:expr),* } => { _inop!(@ $f (and) {$v} $([$t] [$v])*) };
+    { $f:ident ; [$($v:expr),*] in || $t:expr } => { _inop!(@ $f (or) {$t} $([$t] [$v])*) };
+    { $f:ident ; [$v:expr] in || $($t:expr),* } => { _inop!(@ $f (or) {$v} $([$t] [$v])*) };
+    { $f:ident ; [$($v:expr),*] not in && $t:expr } => { _inop!(@ $f (not and) {$t} $([$t] [$v])*) };
+    { $f:ident ; [$v:expr] not in && $($t:expr),* } => { _inop!(@ $f (not and) {$v} $([$t] [$v])*) };
+    { $f:ident ; [$($v:expr),*] not in || $t:expr } => { _inop!(@ $f (not or) {$t} $([$t] [$v])*) };
+    { $f:ident ; [$v:expr] not in || $($t:expr),* } => { _inop!(@ $f (not or) {$v} $([$t] [$v])*) };
+    { $f:ident ; [$($v:expr),*] none in $t:expr } => { _inop!(@ $f (none) {$t} $([$t] [$v])*) };
+    { $f:ident ; [$v:expr] none in $($t:expr),* } => { _inop!(@ $f (none) {$v} $([$t] [$v])*) };
+    { $f:ident ; [$($v:expr),*] count in $t:expr } => { _inop!(@ $f (count) {$t} $([$t] [$v])*) };
+    { $f:ident ; [$v:expr] count in $($t:expr),* } => { _inop!(@ $f (count) {$v} $([$t] [$v])*) };
+    { $f:ident ; [$($v:expr),*] at_least $n:tt in $t:expr } => { _inop!(@ $f (at_least $n) {$t} $([$t] [$v])*) };
+    { $f:ident ; [$v:expr] at_least $n:tt in $($t:expr),* } => { _inop!(@ $f (at_least $n) {$v} $([$t] [$v])*) };
+
+    { @ $f:ident (and) {$k:expr} } => { { let _ = &$k; true } };
+    { @ $f:ident (and) {$k:expr} $([$t:expr] [$v:expr])+ } => { $($t.$f($v))&&* };
+    { @ $f:ident (or) {$k:expr} } => { { let _ = &$k; false } };
+    { @ $f:ident (or) {$k:expr} $([$t:expr] [$v:expr])+ } => { $($t.$f($v))||* };
+    { @ $f:ident (not and) {$k:expr} } => { { let _ = &$k; true } };
+    { @ $f:ident (not and) {$k:expr} $([$t:expr] [$v:expr])+ } => { $(!$t.$f($v))&&* };
+    { @ $f:ident (not or) {$k:expr} } => { { let _ = &$k; false } };
+    { @ $f:ident (not or) {$k:expr} $([$t:expr] [$v:expr])+ } => { $(!$t.$f($v))||* };
+    { @ $f:ident (none) {$k:expr} } => { { let _ = &$k; true } };
+    { @ $f:ident (none) {$k:expr} $([$t:expr] [$v:expr])+ } => { !($($t.$f($v))||*) };
+    { @ $f:ident (count) {$k:expr} } => { { let _ = &$k; 0usize } };
+    { @ $f:ident (count) {$k:expr} $([$t:expr] [$v:expr])+ } => { 0usize $(+ $t.$f($v) as usize)* };
+    { @ $f:ident (at_least $n:tt) {$k:expr} $([$t:expr] [$v:expr])* } => { _inop!(@ $f (count) {$k} $([$t] [$v])*) >= $n };
+}
+#[doc(hidden)]
 #[macro_export]
 macro_rules! _select_op {
     { $x:expr ; $op:tt $a:expr } => { $x $op $a };
@@ -102,11 +288,67 @@ macro_rules! _select_op {
 ///   *equivalent to*
 ///   ```rust
 ///   let mut a = 1;
-///   a = a + 1;
-///   a = 2 - a;
-///   a = a * 3;
+///   {
+///       let (b, c, d) = (1, 2, 3);
+///       a = a + b;
+///       a = c - a;
+///       a = a * d;
+///   }
 ///   # assert_eq!(a, 0);
 ///   ```
+///   All operands are evaluated first, in order, and then applied to the target one by one,
+///   so an operand that reads the target sees its value from before the chain
+///   ```rust
+///   # use libsugar::*;
+///   let mut a = 1;
+///   bop!(= a; + a, * a);
+///   # assert_eq!(a, 2);
+///   ```
+///   - place  
+///     The target can be any place expression, like compound assign it is evaluated only once and after all operands,
+///     the value is read through that borrow so it must be `Copy` unless the target is a plain variable
+///     ```rust
+///     # use libsugar::*;
+///     let mut v = vec![1, 2];
+///     bop!(= v[1]; + 1, * 3);
+///     # assert_eq!(v[1], 9);
+///     ```
+///     ```compile_fail
+///     # use libsugar::*;
+///     let mut v = vec![String::from("a")];
+///     bop!(= v[0]; + "b");
+///     ```
+///     *equivalent to*
+///     ```rust
+///     let mut v = vec![1, 2];
+///     {
+///         let (a, b) = (1, 3);
+///         let x = &mut v[1];
+///         *x = *x + a;
+///         *x = *x * b;
+///     }
+///     # assert_eq!(v[1], 9);
+///     ```
+///   - compound assign  
+///     `+=` `-=` `*=` `/=` `%=` `&=` `|=` `^=` `<<=` `>>=`, the place is evaluated only once and after all operands, so operands may read through it
+///     ```rust
+///     # use libsugar::*;
+///     let mut v = vec![1, 2];
+///     bop!(+= v[1]; 1, 2, 3);
+///     # assert_eq!(v[1], 8);
+///     ```
+///     *equivalent to*
+///     ```rust
+///     let mut v = vec![1, 2];
+///     {
+///         let (a, b, c) = (1, 2, 3);
+///         let x = &mut v[1];
+///         *x += a;
+///         *x += b;
+///         *x += c;
+///     }
+///     # assert_eq!(v[1], 8);
+///     ```
 /// - **Let**
 ///   ```rust
 ///   # use libsugar::*;
@@ -119,6 +361,28 @@ macro_rules! _select_op {
 ///   let a: u8 = 1;
 ///   let mut b = 2;
 ///   ```
+///   - else  
+///     Refutable patterns share one diverging fallback, expands to `let ... else`
+///     (accepted on edition 2018 since rust 1.65)
+///     ```rust
+///     # use libsugar::*;
+///     fn f(x: Option<i32>, y: Result<u8, ()>) -> Result<i32, &'static str> {
+///         bop! { let Some(a)|Option<i32> = x, (Ok(b), c) = (y, 1) else { return Err("none") } }
+///         Ok(a + b as i32 + c)
+///     }
+///     # assert_eq!(f(Some(1), Ok(2)), Ok(4));
+///     # assert_eq!(f(None, Ok(2)), Err("none"));
+///     ```
+///     *equivalent to*
+///     ```rust
+///     fn f(x: Option<i32>, y: Result<u8, ()>) -> Result<i32, &'static str> {
+///         let Some(a): Option<i32> = x else { return Err("none") };
+///         let (Ok(b), c) = (y, 1) else { return Err("none") };
+///         Ok(a + b as i32 + c)
+///     }
+///     # assert_eq!(f(Some(1), Ok(2)), Ok(4));
+///     # assert_eq!(f(None, Ok(2)), Err("none"));
+///     ```
 /// - **Let chain**
 ///   - basic
 ///     ```rust
@@ -222,6 +486,99 @@ macro_rules! _select_op {
 ///     } else  { { 2 }; false };
 ///     # assert!(v);
 ///     ```
+///   - guard  
+///     `if cond` can be placed anywhere in the chain and can use the names bound before it,
+///     a false guard falls through to `else` just like a failed pattern
+///     ```rust
+///     # use libsugar::*;
+///     let a = Some(5);
+///     let b = "6";
+///
+///     let v: i32 = bop!(match && Some(va) = a, if va > 3, Ok(vb) = b.parse::<i32>() => {
+///         va + vb
+///     } else {
+///         0
+///     });
+///     # assert_eq!(v, 11);
+///     ```
+///     *equivalent to*
+///     ```rust
+///     # let a = Some(5);
+///     # let b = "6";
+///     let v: i32 = loop {
+///         if let Some(va) = a {
+///             if va > 3 {
+///                 if let Ok(vb) = b.parse::<i32>() {
+///                     break { va + vb };
+///                 }
+///             }
+///         }
+///         break { 0 };
+///     };
+///     # assert_eq!(v, 11);
+///     ```
+///   - failed link  
+///     `else |f|` receives `(index, label)` of the link that failed, the label is the stringified pattern or guard
+///     (its exact spacing depends on the compiler), or a string given before it like `"name": pat = expr`
+///     ```rust
+///     # use libsugar::*;
+///     let header = Some(1);
+///     let body: Option<i32> = None;
+///
+///     let v: Result<i32, String> = bop!(match && "header": Some(h) = header, Some(b) = body, "positive": if b > 0 => {
+///         Ok(h + b)
+///     } else |(i, what)| {
+///         Err(format!("link {}: expected {}", i, what))
+///     });
+///     # assert!(matches!(&v, Err(e) if e.starts_with("link 1: expected Some")));
+///     ```
+///     *equivalent to*
+///     ```rust
+///     # let header = Some(1);
+///     # let body: Option<i32> = None;
+///     let v: Result<i32, String> = loop {
+///         let fail;
+///         if let Some(h) = header {
+///             if let Some(b) = body {
+///                 if b > 0 {
+///                     break { Ok(h + b) };
+///                 } else { fail = (2, "positive"); }
+///             } else { fail = (1, "Some(b)"); }
+///         } else { fail = (0, "header"); }
+///         let (i, what) = fail;
+///         break { Err(format!("link {}: expected {}", i, what)) };
+///     };
+///     # assert_eq!(v, Err("link 1: expected Some(b)".to_string()));
+///     ```
+///   - `||`  
+///     The first alternative that matches is taken, every alternative must bind the same names  
+///     All four forms above (`bool`, `!loop`, `!loop bool`, lifetime labels) are also available for `||`
+///     ```rust
+///     # use libsugar::*;
+///     let a: Option<i32> = None;
+///     let b: Result<i32, ()> = Ok(2);
+///
+///     let v: i32 = bop!(match || Some(v) = a, Ok(v) = b => {
+///         v
+///     } else {
+///         0
+///     });
+///     # assert_eq!(v, 2);
+///     ```
+///     *equivalent to*
+///     ```rust
+///     # let a: Option<i32> = None;
+///     # let b: Result<i32, ()> = Ok(2);
+///     let v: i32 = loop {
+///         if let Some(v) = a {
+///             break { v };
+///         } else if let Ok(v) = b {
+///             break { v };
+///         }
+///         break { 0 };
+///     };
+///     # assert_eq!(v, 2);
+///     ```
 /// - **In**
 ///   ```rust
 ///   # use libsugar::*;
@@ -256,58 +613,274 @@ macro_rules! _select_op {
 ///     ```ignore
 ///     let c = r.has(&1) && r.has(&2);
 ///     ```
-#[macro_export(local_inner_macros)]
+///   - `not`, `none`, `count`, `at_least`  
+///     All of them accept both shapes and the custom function name  
+///     An empty list is vacuously true for the `&&` forms and `none`, false for the `||` forms and counts `0`
+///     ```rust
+///     # use libsugar::*;
+///     let r = 0..5;
+///     let a = bop!(&1, &7 => not in && r);
+///     let b = bop!(&1, &7 => not in || r);
+///     let c = bop!(&7, &8 => none in r);
+///     let d = bop!(&1, &2, &7 => count in r);
+///     let e = bop!(&1, &2, &7 => at_least 2 in r);
+///     # assert!(!a);
+///     # assert!(b);
+///     # assert!(c);
+///     # assert_eq!(d, 2);
+///     # assert!(e);
+///     ```
+///     *equivalent to*
+///     ```rust
+///     let r = 0..5;
+///     let a = !r.contains(&1) && !r.contains(&7);
+///     let b = !r.contains(&1) || !r.contains(&7);
+///     let c = !(r.contains(&7) || r.contains(&8));
+///     let d = 0usize + r.contains(&1) as usize + r.contains(&2) as usize + r.contains(&7) as usize;
+///     let e = 0usize + r.contains(&1) as usize + r.contains(&2) as usize + r.contains(&7) as usize >= 2;
+///     # assert!(!a);
+///     # assert!(b);
+///     # assert!(c);
+///     # assert_eq!(d, 2);
+///     # assert!(e);
+///     ```
+/// - **Matches**  
+///   Batched `matches!`, one value against many patterns or many values against one pattern, patterns can have `if` guards  
+///   The value is evaluated only once, use `ref` for non-`Copy` bindings like in `matches!`  
+///   A pattern can be an or-pattern like `Some(1) | None`, the `,` separates the alternatives of the batch
+///   ```rust
+///   # use libsugar::*;
+///   let x = Some(5);
+///   let a = bop!(matches || x; Some(1..=3), Some(n) if n > 4, None);
+///   let b = bop!(matches && x; Some(_), Some(n) if n % 2 == 1);
+///   let c = bop!(matches && x, Some(7); Some(n) if n > 4);
+///   # assert!(a);
+///   # assert!(b);
+///   # assert!(c);
+///   ```
+///   *equivalent to*
+///   ```rust
+///   let x = Some(5);
+///   let a = match x {
+///       Some(1..=3) => true,
+///       Some(n) if n > 4 => true,
+///       None => true,
+///       _ => false,
+///   };
+///   let b = {
+///       let x = &x;
+///       matches!(*x, Some(_)) && matches!(*x, Some(n) if n % 2 == 1)
+///   };
+///   let c = matches!(x, Some(n) if n > 4) && matches!(Some(7), Some(n) if n > 4);
+///   # assert!(a);
+///   # assert!(b);
+///   # assert!(c);
+///   ```
+/// - **Fold**  
+///   Reduce operands from left to right
+///   ```rust
+///   # use libsugar::*;
+///   # let (a, b, c) = (3, 1, 2);
+///   # fn gcd(a: u32, b: u32) -> u32 { if b == 0 { a } else { gcd(b, a % b) } }
+///   let s = bop!(+ a, b, c);
+///   let p = bop!(* a, b, c);
+///   let m = bop!(min a, b, c);
+///   let n = bop!(max a, b, c);
+///   let g = bop!(fold gcd; 12, 18, 27);
+///   # assert_eq!((s, p, m, n, g), (6, 6, 1, 3, 3));
+///   ```
+///   *equivalent to*
+///   ```rust
+///   # let (a, b, c) = (3, 1, 2);
+///   # fn gcd(a: u32, b: u32) -> u32 { if b == 0 { a } else { gcd(b, a % b) } }
+///   let s = a + b + c;
+///   let p = a * b * c;
+///   let m = core::cmp::min(core::cmp::min(a, b), c);
+///   let n = core::cmp::max(core::cmp::max(a, b), c);
+///   let g = gcd(gcd(12, 18), 27);
+///   # assert_eq!((s, p, m, n, g), (6, 6, 1, 3, 3));
+///   ```
+///   - `PartialOrd`  
+///     `partial_min` `partial_max` skip NaN like [`f64::min`], the result is NaN only if every operand is NaN  
+///     `nan_min` `nan_max` propagate NaN, the result is the first NaN if there is any  
+///     A value is treated as NaN when it is not comparable with itself, ties keep the earlier operand
+///     ```rust
+///     # use libsugar::*;
+///     let a = bop!(partial_min 2.0, f64::NAN, 1.0);
+///     let b = bop!(nan_max 2.0, f64::NAN, 1.0);
+///     # assert_eq!(a, 1.0);
+///     # assert!(b.is_nan());
+///     ```
+/// - **Cmp**  
+///   Chained comparisons like python, each operand is evaluated at most once and the chain short-circuits like `&&`  
+///   Operands are borrowed, so places like `self.a` or a `String` can still be used after the chain  
+///   Operands containing `<` or `>` (like turbofish) need to be wrapped in parentheses
+///   ```rust
+///   # use libsugar::*;
+///   # let v: Vec<i32> = Vec::with_capacity(8);
+///   let i = 0;
+///   let c = bop!(cmp 0 <= i < v.len() + 1 <= v.capacity());
+///   # assert!(c);
+///   ```
+///   *equivalent to*
+///   ```rust
+///   # let v: Vec<i32> = Vec::with_capacity(8);
+///   let i = 0;
+///   let c = {
+///       let a = &0;
+///       let b = &i;
+///       *a <= *b && {
+///           let c = &(v.len() + 1);
+///           *b < *c && *c <= v.capacity()
+///       }
+///   };
+///   # assert!(c);
+///   ```
+#[macro_export]
 macro_rules! bop {
+    { $($t:tt)* } => { $crate::_bop_backend!{ $($t)* } };
+}
+#[cfg(not(feature = "proc-macro"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _bop_backend {
+    { $($t:tt)* } => { $crate::_bop!{ $($t)* } };
+}
+#[cfg(feature = "proc-macro")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _bop_backend {
+    { $($t:tt)* } => { $crate::_bop_proc!{ $crate ; $($t)* } };
+}
+#[cfg(feature = "proc-macro")]
+#[doc(hidden)]
+pub use libsugar_macros::bop as _bop_proc;
+#[doc(hidden)]
+#[macro_export(local_inner_macros)]
+macro_rules! _bop {
     {} => { };
 
     // let op
     { let $($p:pat $(| $t:ty)? $(= $e:expr)?),*} => { $(let $p $(: $t)? $(= $e)?;)* };
+    { let $($t:tt)+ } => { _letelse!{ [] $($t)+ } };
 
     // if let op
-    { $($l:lifetime :)? match && $($p:pat = $e:expr),* => $b:block else $el:block } => {
-        $($l :)? loop { _matchand!( $( $p = $e ; { } )* ; { break $($l)? $b ; }) ; break $($l)? $el; }
+    { $($l:lifetime :)? match && $($t:tt)* } => { _letchain!{ (loop $($l)?) [] $($t)* } };
+    { bool $($l:lifetime :)? match && $($t:tt)* } => { _letchain!{ (bool $($l)?) [] $($t)* } };
+    { !loop match && $($t:tt)* } => { _letchain!{ (!loop) [] $($t)* } };
+    { !loop bool match && $($t:tt)* } => { _letchain!{ (!loop bool) [] $($t)* } };
+    { $($l:lifetime :)? match || $($p:pat = $e:expr),* => $b:block else $el:block } => {
+        $($l :)? loop { _matchor!( $( $p = $e ; { } )* ; { break $($l)? ($b) ; }) ; break $($l)? ($el); }
     };
-    { bool $($l:lifetime :)? match && $($p:pat = $e:expr),* => $b:block $(else $el:block)? } => {
-        $($l :)? loop { _matchand!( $( $p = $e ; { } )* ; { $b ; break $($l)? true; }) ; $($el ;)? break $($l)? false; }
+    { bool $($l:lifetime :)? match || $($p:pat = $e:expr),* => $b:block $(else $el:block)? } => {
+        $($l :)? loop { _matchor!( $( $p = $e ; { } )* ; { $b ; break $($l)? true; }) ; $($el ;)? break $($l)? false; }
     };
-    { !loop match && $($p:pat = $e:expr),* => $b:block else $el:block } => {
-        _matchand!( $( $p = $e ; { } )* ; { $b } { $el })
+    { !loop match || $($p:pat = $e:expr),* => $b:block else $el:block } => {
+        _matchor!( $( $p = $e ; { } )* ; { $b } { $el })
     };
-    { !loop bool match && $($p:pat = $e:expr),* => $b:block $(else $el:block)? } => {
-        _matchand!( $( $p = $e ; { } )* ; { $b ; true } { $($el ;)? false })
+    { !loop bool match || $($p:pat = $e:expr),* => $b:block $(else $el:block)? } => {
+        _matchor!( $( $p = $e ; { } )* ; { $b ; true } { $($el ;)? false })
     };
 
+    // inop with custom function name
+    { $fname:ident ; $($v:expr),* => $($m:tt)+ } => { _inop!($fname ; [$($v),*] $($m)+) };
+
+    // matches
+    { matches || $($x:expr),+ ; $p:pat } => { $(::core::matches!($x, $p))||* };
+    { matches && $($x:expr),+ ; $p:pat } => { $(::core::matches!($x, $p))&&* };
+    { matches || $($x:expr),+ ; $p:pat if $g:expr } => { $(::core::matches!($x, $p if $g))||* };
+    { matches && $($x:expr),+ ; $p:pat if $g:expr } => { $(::core::matches!($x, $p if $g))&&* };
+    { matches || $($x:expr),+ ; $($p:pat)|+ $(if $g:expr)? } => { _matchop!(|| [$($p)|+ $(if $g)?] $($x),+) };
+    { matches && $($x:expr),+ ; $($p:pat)|+ $(if $g:expr)? } => { _matchop!(&& [$($p)|+ $(if $g)?] $($x),+) };
+    { matches || $x:expr ; $($($p:pat)|+ $(if $g:expr)?),+ $(,)? } => { match $x { $($($p)|+ $(if $g)? => true,)+ _ => false } };
+    { matches && $x:expr ; $($($p:pat)|+ $(if $g:expr)?),+ $(,)? } => { { let x = &$x; $(::core::matches!(*x, $($p)|+ $(if $g)?))&&* } };
+
+    // fold
+    { min $a:expr $(, $b:expr)* $(,)? } => { { let v = $a; $(let v = ::core::cmp::min(v, $b);)* v } };
+    { max $a:expr $(, $b:expr)* $(,)? } => { { let v = $a; $(let v = ::core::cmp::max(v, $b);)* v } };
+    { partial_min $a:expr $(, $b:expr)* $(,)? } => { { let v = $a; $(let v = $crate::_fold::partial_min(v, $b);)* v } };
+    { partial_max $a:expr $(, $b:expr)* $(,)? } => { { let v = $a; $(let v = $crate::_fold::partial_max(v, $b);)* v } };
+    { nan_min $a:expr $(, $b:expr)* $(,)? } => { { let v = $a; $(let v = $crate::_fold::nan_min(v, $b);)* v } };
+    { nan_max $a:expr $(, $b:expr)* $(,)? } => { { let v = $a; $(let v = $crate::_fold::nan_max(v, $b);)* v } };
+    { fold $f:expr ; $a:expr $(, $b:expr)* $(,)? } => { { let f = $f; let v = $a; $(let v = f(v, $b);)* v } };
+    { + $a:expr $(, $b:expr)+ $(,)? } => { $a $(+ $b)+ };
+    { * $a:expr $(, $b:expr)+ $(,)? } => { $a $(* $b)+ };
+
+    // cmp chain
+    { cmp $($t:tt)+ } => { _cmpchain!([] $($t)+) };
+
     // base op
     { $x:expr $(;)? } => { $x };
     { || $x:expr $(;)? } => { $x };
     { && $x:expr $(;)? } => { $x };
-    { = $x:ident $(;)? } => { };
+    { = $x:expr $(;)? } => { };
     { || $x:expr ; $($op:tt $a:expr $(;$n:tt)?),* } => { $(_select_op!($x; $op $a $(;$n)?))||* };
     { && $x:expr ; $($op:tt $a:expr $(;$n:tt)?),* } => { $(_select_op!($x; $op $a $(;$n)?))&&* };
-    { = $x:ident ; $($op:tt $a:expr $(;$n:tt)?),* } => { $($x = _select_op!($x; $op $a $(;$n)?));* ; };
+    { = $x:ident ; $($op:tt $a:expr $(;$n:tt)?),* } => { _setop!(@var $x [] $($op $a $(;$n)?),*) };
+    { = $x:expr ; $($op:tt $a:expr $(;$n:tt)?),* } => { _setop!(($x) [] $($op $a $(;$n)?),*) };
     // batch op
     { || $x:expr ; $op:tt $(;)? } => { $x };
     { && $x:expr ; $op:tt $(;)? } => { $x };
-    { = $x:ident ; $op:tt $(;)? } => { };
+    { = $x:expr ; $op:tt $(;)? } => { };
     { || $x:expr ; $op:tt ; $($a:expr $(;$n:tt)?),* } => { $(_select_op!($x; $op $a $(;$n)?))||* };
-    { && $x:expr ; $op:tt ; $($a:expr $(;$n:tt)?),* } => { $(_select_op!($x; $op $a $(;$n)?))||* };
-    { = $x:ident ; $op:tt ; $($a:expr $(;$n:tt)?),* } => { $($x = _select_op!($x; $op $a $(;$n)?));* ; };
+    { && $x:expr ; $op:tt ; $($a:expr $(;$n:tt)?),* } => { $(_select_op!($x; $op $a $(;$n)?))&&* };
+    { = $x:ident ; $op:tt ; $($a:expr $(;$n:tt)?),* } => { _setop!(@var $x [] $($op $a $(;$n)?),*) };
+    { = $x:expr ; $op:tt ; $($a:expr $(;$n:tt)?),* } => { _setop!(($x) [] $($op $a $(;$n)?),*) };
+
+    // compound assign
+    { += $x:expr ; $($a:expr),+ $(,)? } => { _compound!(+= ($x) [] $($a),+) };
+    { -= $x:expr ; $($a:expr),+ $(,)? } => { _compound!(-= ($x) [] $($a),+) };
+    { *= $x:expr ; $($a:expr),+ $(,)? } => { _compound!(*= ($x) [] $($a),+) };
+    { /= $x:expr ; $($a:expr),+ $(,)? } => { _compound!(/= ($x) [] $($a),+) };
+    { %= $x:expr ; $($a:expr),+ $(,)? } => { _compound!(%= ($x) [] $($a),+) };
+    { &= $x:expr ; $($a:expr),+ $(,)? } => { _compound!(&= ($x) [] $($a),+) };
+    { |= $x:expr ; $($a:expr),+ $(,)? } => { _compound!(|= ($x) [] $($a),+) };
+    { ^= $x:expr ; $($a:expr),+ $(,)? } => { _compound!(^= ($x) [] $($a),+) };
+    { <<= $x:expr ; $($a:expr),+ $(,)? } => { _compound!(<<= ($x) [] $($a),+) };
+    { >>= $x:expr ; $($a:expr),+ $(,)? } => { _compound!(>>= ($x) [] $($a),+) };
 
     // inop
-    { $fname:ident ; $($v:expr),* => in && $t:expr } => { $($t.$fname($v))&&* };
-    { $fname:ident ; $($v:expr),* => in || $t:expr } => { $($t.$fname($v))||* };
-    { $fname:ident ; $v:expr => in && $($t:expr),* } => { $($t.$fname($v))&&* };
-    { $fname:ident ; $v:expr => in || $($t:expr),* } => { $($t.$fname($v))||* };
-
-    { $($fname:ident ;)? => in && $t:expr  } => { false };
-    { $($fname:ident ;)? => in || $t:expr  } => { false };
-    { $($fname:ident ;)? $v:expr => in && } => { false };
-    { $($fname:ident ;)? $v:expr => in || } => { false };
-
-    { $($v:expr),* => in && $t:expr } => { $($t.contains($v))&&* };
-    { $($v:expr),* => in || $t:expr } => { $($t.contains($v))||* };
-    { $v:expr => in && $($t:expr),* } => { $($t.contains($v))&&* };
-    { $v:expr => in || $($t:expr),* } => { $($t.contains($v))||* };
+    { $($v:expr),* => $($m:tt)+ } => { _inop!(contains ; [$($v),*] $($m)+) };
+}
+
+#[doc(hidden)]
+pub mod _fold {
+    use core::cmp::Ordering;
+
+    #[inline]
+    fn pick<T: PartialOrd>(a: T, b: T, ord: Ordering, propagate_nan: bool) -> T {
+        match b.partial_cmp(&a) {
+            Some(o) if o == ord => b,
+            Some(_) => a,
+            None => {
+                if a.partial_cmp(&a).is_none() != propagate_nan {
+                    b
+                } else {
+                    a
+                }
+            }
+        }
+    }
+
+    #[inline]
+    pub fn partial_min<T: PartialOrd>(a: T, b: T) -> T {
+        pick(a, b, Ordering::Less, false)
+    }
+
+    #[inline]
+    pub fn partial_max<T: PartialOrd>(a: T, b: T) -> T {
+        pick(a, b, Ordering::Greater, false)
+    }
+
+    #[inline]
+    pub fn nan_min<T: PartialOrd>(a: T, b: T) -> T {
+        pick(a, b, Ordering::Less, true)
+    }
+
+    #[inline]
+    pub fn nan_max<T: PartialOrd>(a: T, b: T) -> T {
+        pick(a, b, Ordering::Greater, true)
+    }
 }
 
 #[cfg(feature = "side-effect")]
@@ -1,3 +1,752 @@
+use core::cmp::Ordering;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::hash::{BuildHasherDefault, Hasher};
+
+#[doc(hidden)]
+pub mod _alloc {
+    extern crate alloc;
+    pub use alloc::boxed::Box;
+    pub use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+    pub use alloc::rc::Rc;
+    pub use alloc::string::String;
+    pub use alloc::sync::Arc;
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+    #[cfg(feature = "std")]
+    pub use std::collections::{HashMap, HashSet};
+}
+
+#[doc(hidden)]
+pub mod _reserve {
+    #[cfg(feature = "std")]
+    use super::_alloc::{HashMap, HashSet};
+    use super::_alloc::{String, Vec, VecDeque};
+    #[cfg(feature = "std")]
+    use core::hash::{BuildHasher, Hash};
+
+    /// Reserves on targets that support it, see [`_ReserveNone`] for the rest
+    pub struct Reserve<'a, T>(pub &'a mut T);
+
+    pub trait _ReserveSome {
+        fn _reserve(&mut self, additional: usize);
+    }
+
+    pub trait _ReserveNone {
+        fn _reserve(&mut self, additional: usize);
+    }
+
+    impl<T> _ReserveNone for &mut Reserve<'_, T> {
+        #[inline]
+        fn _reserve(&mut self, _: usize) {}
+    }
+
+    macro_rules! reserve {
+        ($(impl<$($g:ident $(: $b0:ident $(+ $b:ident)*)?),*> $t:ty;)*) => {$(
+            impl<$($g $(: $b0 $(+ $b)*)?),*> _ReserveSome for Reserve<'_, $t> {
+                #[inline]
+                fn _reserve(&mut self, additional: usize) {
+                    self.0.reserve(additional)
+                }
+            }
+            impl<$($g $(: $b0 $(+ $b)*)?),*> _ReserveSome for Reserve<'_, &mut $t> {
+                #[inline]
+                fn _reserve(&mut self, additional: usize) {
+                    self.0.reserve(additional)
+                }
+            }
+        )*};
+    }
+
+    reserve! {
+        impl<> String;
+        impl<T> Vec<T>;
+        impl<T> VecDeque<T>;
+    }
+    #[cfg(feature = "std")]
+    reserve! {
+        impl<K: Eq + Hash, V, S: BuildHasher> HashMap<K, V, S>;
+        impl<T: Eq + Hash, S: BuildHasher> HashSet<T, S>;
+    }
+}
+
+#[doc(hidden)]
+pub mod _multi {
+    use super::_alloc::{BTreeMap, BTreeSet, Vec, VecDeque};
+    #[cfg(feature = "std")]
+    use super::_alloc::{HashMap, HashSet};
+    #[cfg(feature = "std")]
+    use core::hash::{BuildHasher, Hash};
+
+    /// Inner container of a multimap
+    pub trait _MultiValue<V>: Default {
+        fn _add(&mut self, value: V);
+    }
+
+    impl<V> _MultiValue<V> for Vec<V> {
+        #[inline]
+        fn _add(&mut self, value: V) {
+            self.push(value)
+        }
+    }
+
+    impl<V> _MultiValue<V> for VecDeque<V> {
+        #[inline]
+        fn _add(&mut self, value: V) {
+            self.push_back(value)
+        }
+    }
+
+    impl<V: Ord> _MultiValue<V> for BTreeSet<V> {
+        #[inline]
+        fn _add(&mut self, value: V) {
+            self.insert(value);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<V: Eq + Hash, S: BuildHasher + Default> _MultiValue<V> for HashSet<V, S> {
+        #[inline]
+        fn _add(&mut self, value: V) {
+            self.insert(value);
+        }
+    }
+
+    /// Map whose values are [`_MultiValue`] containers
+    pub trait _MultiMap<K, V> {
+        type Inner: _MultiValue<V>;
+        fn _multi_insert(&mut self, key: K, value: V);
+    }
+
+    impl<K: Ord, V, C: _MultiValue<V>> _MultiMap<K, V> for BTreeMap<K, C> {
+        type Inner = C;
+        #[inline]
+        fn _multi_insert(&mut self, key: K, value: V) {
+            self.entry(key).or_default()._add(value)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<K: Eq + Hash, V, C: _MultiValue<V>, S: BuildHasher> _MultiMap<K, V> for HashMap<K, C, S> {
+        type Inner = C;
+        #[inline]
+        fn _multi_insert(&mut self, key: K, value: V) {
+            self.entry(key).or_default()._add(value)
+        }
+    }
+
+    /// Pins the inner container type of `_m` to `C`
+    #[inline]
+    pub fn _inner<M: _MultiMap<K, V, Inner = C>, K, V, C>(_m: &M) {}
+}
+
+#[doc(hidden)]
+pub mod _set_ops {
+    use super::_alloc::BTreeSet;
+    #[cfg(feature = "std")]
+    use super::_alloc::HashSet;
+    #[cfg(feature = "std")]
+    use core::hash::{BuildHasher, Hash};
+    use core::ops::{BitAnd, BitOr, BitXor, Sub};
+
+    /// Set type that can be an operand of `set_ops!`
+    pub trait SetKind: Default {
+        type Item: Clone;
+        fn has(&self, x: &Self::Item) -> bool;
+        fn add(&mut self, x: Self::Item);
+        fn each<'a, F: FnMut(&'a Self::Item)>(&'a self, f: F);
+    }
+
+    impl<T: Ord + Clone> SetKind for BTreeSet<T> {
+        type Item = T;
+        #[inline]
+        fn has(&self, x: &T) -> bool {
+            self.contains(x)
+        }
+        #[inline]
+        fn add(&mut self, x: T) {
+            self.insert(x);
+        }
+        #[inline]
+        fn each<'a, F: FnMut(&'a T)>(&'a self, f: F) {
+            self.iter().for_each(f)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Eq + Hash + Clone, S: BuildHasher + Default> SetKind for HashSet<T, S> {
+        type Item = T;
+        #[inline]
+        fn has(&self, x: &T) -> bool {
+            self.contains(x)
+        }
+        #[inline]
+        fn add(&mut self, x: T) {
+            self.insert(x);
+        }
+        #[inline]
+        fn each<'a, F: FnMut(&'a T)>(&'a self, f: F) {
+            self.iter().for_each(f)
+        }
+    }
+
+    /// Node of a set expression, `each` visits every element that may be in the result
+    pub trait SetExpr<'a> {
+        type Set: SetKind + 'a;
+        fn has(&self, x: &<Self::Set as SetKind>::Item) -> bool;
+        fn each<F: FnMut(&'a <Self::Set as SetKind>::Item)>(&self, f: &mut F);
+    }
+
+    pub struct Expr<E>(pub E);
+    pub struct Leaf<'a, S>(pub &'a S);
+    pub struct Union<L, R>(L, R);
+    pub struct Inter<L, R>(L, R);
+    pub struct Diff<L, R>(L, R);
+    pub struct SymDiff<L, R>(L, R);
+
+    impl<'a, S: SetKind> SetExpr<'a> for Leaf<'a, S> {
+        type Set = S;
+        #[inline]
+        fn has(&self, x: &S::Item) -> bool {
+            self.0.has(x)
+        }
+        #[inline]
+        fn each<F: FnMut(&'a S::Item)>(&self, f: &mut F) {
+            self.0.each(f)
+        }
+    }
+
+    macro_rules! node {
+        ($name:ident, $trait:ident, $fn:ident, |$s:ident, $x:ident| $has:expr, |$e:ident, $f:ident| $each:expr) => {
+            impl<'a, L: SetExpr<'a>, R: SetExpr<'a, Set = L::Set>> SetExpr<'a> for $name<L, R> {
+                type Set = L::Set;
+                #[inline]
+                fn has(&self, $x: &<L::Set as SetKind>::Item) -> bool {
+                    let $s = self;
+                    $has
+                }
+                #[inline]
+                fn each<F: FnMut(&'a <L::Set as SetKind>::Item)>(&self, $f: &mut F) {
+                    let $e = self;
+                    $each
+                }
+            }
+
+            impl<L, R> $trait<Expr<R>> for Expr<L> {
+                type Output = Expr<$name<L, R>>;
+                #[inline]
+                fn $fn(self, rhs: Expr<R>) -> Self::Output {
+                    Expr($name(self.0, rhs.0))
+                }
+            }
+        };
+    }
+
+    node!(
+        Union,
+        BitOr,
+        bitor,
+        |s, x| s.0.has(x) || s.1.has(x),
+        |e, f| {
+            e.0.each(f);
+            e.1.each(f)
+        }
+    );
+    node!(
+        Inter,
+        BitAnd,
+        bitand,
+        |s, x| s.0.has(x) && s.1.has(x),
+        |e, f| e.0.each(f)
+    );
+    node!(Diff, Sub, sub, |s, x| s.0.has(x) && !s.1.has(x), |e, f| {
+        e.0.each(f)
+    });
+    node!(
+        SymDiff,
+        BitXor,
+        bitxor,
+        |s, x| s.0.has(x) != s.1.has(x),
+        |e, f| {
+            e.0.each(f);
+            e.1.each(f)
+        }
+    );
+
+    /// Collects the elements of `e` into a single new set
+    pub fn eval<'a, E: SetExpr<'a>>(e: Expr<E>) -> E::Set {
+        let mut out = E::Set::default();
+        e.0.each(&mut |x| {
+            if e.0.has(x) {
+                out.add(x.clone())
+            }
+        });
+        out
+    }
+}
+
+#[doc(hidden)]
+pub mod _extend {
+    pub trait _ExtendOne<A> {
+        fn _extend_one(&mut self, item: A);
+    }
+
+    impl<A, T: Extend<A> + ?Sized> _ExtendOne<A> for T {
+        #[inline]
+        fn _extend_one(&mut self, item: A) {
+            self.extend(Some(item))
+        }
+    }
+
+    pub trait _ExtendPair<K, V> {
+        fn _extend_pair(&mut self, key: K, value: V);
+    }
+
+    impl<K, V, T: Extend<(K, V)> + ?Sized> _ExtendPair<K, V> for T {
+        #[inline]
+        fn _extend_pair(&mut self, key: K, value: V) {
+            self.extend(Some((key, value)))
+        }
+    }
+}
+
+/// Reserves `additional` on `$m` when its type supports it
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _reserve {
+    ($m:ident, $additional:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::_reserve::{_ReserveNone, _ReserveSome};
+        (&mut $crate::_reserve::Reserve(&mut $m))._reserve($additional);
+    }};
+}
+
+/// Number of items, known at compile time
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _count {
+    (@ $e:expr) => { () };
+    ($($e:expr),*) => { <[()]>::len(&[$($crate::_count!(@ $e)),*]) };
+}
+
+/// Inserts items, `..iterable` spreads and conditional entries in order, plain items in between are reserved together  
+/// recursion only steps once per eight plain entries and once per spread or conditional entry, so large tables stay under the recursion limit
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _spread {
+    (@push $c:ident $f:ident [$($p:expr),*]) => {
+        $crate::_reserve!($c, $crate::_count!($($p),*));
+        $($c.$f($p);)*
+    };
+    (@push $c:ident $f:ident [$($p:expr),*] .. $s:expr $(, $($rest:tt)*)?) => {
+        $crate::_spread!(@push $c $f [$($p),*]);
+        {
+            let it = ::core::iter::IntoIterator::into_iter($s);
+            $crate::_reserve!($c, ::core::iter::Iterator::size_hint(&it).0);
+            for x in it {
+                $c.$f(x);
+            }
+        }
+        $crate::_spread!(@push $c $f [] $($($rest)*)?);
+    };
+    // plain entries are taken eight at a time, a `..` among them is found first so it is not parsed as a range
+    (@push $c:ident $f:ident [$($p:expr),*] $e1:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@push $c $f [$($p,)* $e1] .. $($rest)*);
+    };
+    (@push $c:ident $f:ident [$($p:expr),*] $e1:expr, $e2:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@push $c $f [$($p,)* $e1, $e2] .. $($rest)*);
+    };
+    (@push $c:ident $f:ident [$($p:expr),*] $e1:expr, $e2:expr, $e3:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@push $c $f [$($p,)* $e1, $e2, $e3] .. $($rest)*);
+    };
+    (@push $c:ident $f:ident [$($p:expr),*] $e1:expr, $e2:expr, $e3:expr, $e4:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@push $c $f [$($p,)* $e1, $e2, $e3, $e4] .. $($rest)*);
+    };
+    (@push $c:ident $f:ident [$($p:expr),*] $e1:expr, $e2:expr, $e3:expr, $e4:expr, $e5:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@push $c $f [$($p,)* $e1, $e2, $e3, $e4, $e5] .. $($rest)*);
+    };
+    (@push $c:ident $f:ident [$($p:expr),*] $e1:expr, $e2:expr, $e3:expr, $e4:expr, $e5:expr, $e6:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@push $c $f [$($p,)* $e1, $e2, $e3, $e4, $e5, $e6] .. $($rest)*);
+    };
+    (@push $c:ident $f:ident [$($p:expr),*] $e1:expr, $e2:expr, $e3:expr, $e4:expr, $e5:expr, $e6:expr, $e7:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@push $c $f [$($p,)* $e1, $e2, $e3, $e4, $e5, $e6, $e7] .. $($rest)*);
+    };
+    (@push $c:ident $f:ident [$($p:expr),*] $e1:expr, $e2:expr, $e3:expr, $e4:expr, $e5:expr, $e6:expr, $e7:expr, $e8:expr $(, $($rest:tt)*)?) => {
+        $crate::_spread!(@push $c $f [$($p,)* $e1, $e2, $e3, $e4, $e5, $e6, $e7, $e8] $($($rest)*)?);
+    };
+    (@push $c:ident $f:ident [$($p:expr),*] $e:expr $(, $($rest:tt)*)?) => {
+        $crate::_spread!(@push $c $f [$($p,)* $e] $($($rest)*)?);
+    };
+    (@push $c:ident $f:ident [$($p:expr),*] $($t:tt)+) => {
+        $crate::_spread!(@push_if $c $f [$($p),*] [] $($t)+);
+    };
+    (@push_if $c:ident $f:ident [$($p:expr),*] [$($e:tt)+] if $cond:expr $(, $($rest:tt)*)?) => {
+        $crate::_spread!(@push $c $f [$($p),*]);
+        if $cond {
+            $c.$f($($e)+);
+        }
+        $crate::_spread!(@push $c $f [] $($($rest)*)?);
+    };
+    // `expr` may not be followed by `if`, so the value is gathered as tokens, four at a time up to the next `if`
+    (@push_if $c:ident $f:ident [$($p:expr),*] [$($e:tt)*] $t1:tt if $($rest:tt)*) => {
+        $crate::_spread!(@push_if $c $f [$($p),*] [$($e)* $t1] if $($rest)*);
+    };
+    (@push_if $c:ident $f:ident [$($p:expr),*] [$($e:tt)*] $t1:tt $t2:tt if $($rest:tt)*) => {
+        $crate::_spread!(@push_if $c $f [$($p),*] [$($e)* $t1 $t2] if $($rest)*);
+    };
+    (@push_if $c:ident $f:ident [$($p:expr),*] [$($e:tt)*] $t1:tt $t2:tt $t3:tt if $($rest:tt)*) => {
+        $crate::_spread!(@push_if $c $f [$($p),*] [$($e)* $t1 $t2 $t3] if $($rest)*);
+    };
+    (@push_if $c:ident $f:ident [$($p:expr),*] [$($e:tt)*] $t1:tt $t2:tt $t3:tt $t4:tt $($rest:tt)*) => {
+        $crate::_spread!(@push_if $c $f [$($p),*] [$($e)* $t1 $t2 $t3 $t4] $($rest)*);
+    };
+    (@push_if $c:ident $f:ident [$($p:expr),*] [$($e:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::_spread!(@push_if $c $f [$($p),*] [$($e)* $t] $($rest)*);
+    };
+
+    (@pair $c:ident $f:ident [$(($k:expr, $v:expr))*]) => {
+        $crate::_reserve!($c, $crate::_count!($($k),*));
+        $($c.$f($k, $v);)*
+    };
+    (@pair $c:ident $f:ident [$($p:tt)*] .. $s:expr $(, $($rest:tt)*)?) => {
+        $crate::_spread!(@pair $c $f [$($p)*]);
+        {
+            let it = ::core::iter::IntoIterator::into_iter($s);
+            $crate::_reserve!($c, ::core::iter::Iterator::size_hint(&it).0);
+            for (k, v) in it {
+                $c.$f(k, v);
+            }
+        }
+        $crate::_spread!(@pair $c $f [] $($($rest)*)?);
+    };
+    // pairs are taken eight at a time like `@push`
+    (@pair $c:ident $f:ident [$($p:tt)*] $k1:expr => $v1:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@pair $c $f [$($p)* ($k1, $v1)] .. $($rest)*);
+    };
+    (@pair $c:ident $f:ident [$($p:tt)*] $k1:expr => $v1:expr, $k2:expr => $v2:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@pair $c $f [$($p)* ($k1, $v1) ($k2, $v2)] .. $($rest)*);
+    };
+    (@pair $c:ident $f:ident [$($p:tt)*] $k1:expr => $v1:expr, $k2:expr => $v2:expr, $k3:expr => $v3:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@pair $c $f [$($p)* ($k1, $v1) ($k2, $v2) ($k3, $v3)] .. $($rest)*);
+    };
+    (@pair $c:ident $f:ident [$($p:tt)*] $k1:expr => $v1:expr, $k2:expr => $v2:expr, $k3:expr => $v3:expr, $k4:expr => $v4:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@pair $c $f [$($p)* ($k1, $v1) ($k2, $v2) ($k3, $v3) ($k4, $v4)] .. $($rest)*);
+    };
+    (@pair $c:ident $f:ident [$($p:tt)*] $k1:expr => $v1:expr, $k2:expr => $v2:expr, $k3:expr => $v3:expr, $k4:expr => $v4:expr, $k5:expr => $v5:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@pair $c $f [$($p)* ($k1, $v1) ($k2, $v2) ($k3, $v3) ($k4, $v4) ($k5, $v5)] .. $($rest)*);
+    };
+    (@pair $c:ident $f:ident [$($p:tt)*] $k1:expr => $v1:expr, $k2:expr => $v2:expr, $k3:expr => $v3:expr, $k4:expr => $v4:expr, $k5:expr => $v5:expr, $k6:expr => $v6:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@pair $c $f [$($p)* ($k1, $v1) ($k2, $v2) ($k3, $v3) ($k4, $v4) ($k5, $v5) ($k6, $v6)] .. $($rest)*);
+    };
+    (@pair $c:ident $f:ident [$($p:tt)*] $k1:expr => $v1:expr, $k2:expr => $v2:expr, $k3:expr => $v3:expr, $k4:expr => $v4:expr, $k5:expr => $v5:expr, $k6:expr => $v6:expr, $k7:expr => $v7:expr, .. $($rest:tt)*) => {
+        $crate::_spread!(@pair $c $f [$($p)* ($k1, $v1) ($k2, $v2) ($k3, $v3) ($k4, $v4) ($k5, $v5) ($k6, $v6) ($k7, $v7)] .. $($rest)*);
+    };
+    (@pair $c:ident $f:ident [$($p:tt)*] $k1:expr => $v1:expr, $k2:expr => $v2:expr, $k3:expr => $v3:expr, $k4:expr => $v4:expr, $k5:expr => $v5:expr, $k6:expr => $v6:expr, $k7:expr => $v7:expr, $k8:expr => $v8:expr $(, $($rest:tt)*)?) => {
+        $crate::_spread!(@pair $c $f [$($p)* ($k1, $v1) ($k2, $v2) ($k3, $v3) ($k4, $v4) ($k5, $v5) ($k6, $v6) ($k7, $v7) ($k8, $v8)] $($($rest)*)?);
+    };
+    (@pair $c:ident $f:ident [$($p:tt)*] $k:expr => $v:expr $(, $($rest:tt)*)?) => {
+        $crate::_spread!(@pair $c $f [$($p)* ($k, $v)] $($($rest)*)?);
+    };
+    (@pair $c:ident $f:ident [$($p:tt)*] $k:expr =>? $o:expr $(, $($rest:tt)*)?) => {
+        $crate::_spread!(@pair $c $f [$($p)*]);
+        if let ::core::option::Option::Some(v) = $o {
+            $c.$f($k, v);
+        }
+        $crate::_spread!(@pair $c $f [] $($($rest)*)?);
+    };
+    (@pair $c:ident $f:ident [$($p:tt)*] $k:expr => $($t:tt)+) => {
+        $crate::_spread!(@pair_if $c $f [$($p)*] ($k) [] $($t)+);
+    };
+    (@pair_if $c:ident $f:ident [$($p:tt)*] ($k:expr) [$($v:tt)+] if $cond:expr $(, $($rest:tt)*)?) => {
+        $crate::_spread!(@pair $c $f [$($p)*]);
+        if $cond {
+            $c.$f($k, $($v)+);
+        }
+        $crate::_spread!(@pair $c $f [] $($($rest)*)?);
+    };
+    (@pair_if $c:ident $f:ident [$($p:tt)*] ($k:expr) [$($v:tt)*] $t1:tt if $($rest:tt)*) => {
+        $crate::_spread!(@pair_if $c $f [$($p)*] ($k) [$($v)* $t1] if $($rest)*);
+    };
+    (@pair_if $c:ident $f:ident [$($p:tt)*] ($k:expr) [$($v:tt)*] $t1:tt $t2:tt if $($rest:tt)*) => {
+        $crate::_spread!(@pair_if $c $f [$($p)*] ($k) [$($v)* $t1 $t2] if $($rest)*);
+    };
+    (@pair_if $c:ident $f:ident [$($p:tt)*] ($k:expr) [$($v:tt)*] $t1:tt $t2:tt $t3:tt if $($rest:tt)*) => {
+        $crate::_spread!(@pair_if $c $f [$($p)*] ($k) [$($v)* $t1 $t2 $t3] if $($rest)*);
+    };
+    (@pair_if $c:ident $f:ident [$($p:tt)*] ($k:expr) [$($v:tt)*] $t1:tt $t2:tt $t3:tt $t4:tt $($rest:tt)*) => {
+        $crate::_spread!(@pair_if $c $f [$($p)*] ($k) [$($v)* $t1 $t2 $t3 $t4] $($rest)*);
+    };
+    (@pair_if $c:ident $f:ident [$($p:tt)*] ($k:expr) [$($v:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::_spread!(@pair_if $c $f [$($p)*] ($k) [$($v)* $t] $($rest)*);
+    };
+}
+
+/// Unwraps the result of `try_map!` or `try_set!`, panicking with the duplicate
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _strict {
+    ($r:expr) => {
+        match $r {
+            ::core::result::Result::Ok(v) => v,
+            ::core::result::Result::Err(e) => ::core::panic!("{}", e),
+        }
+    };
+}
+
+/// Deterministic 64 bit [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) hasher  
+/// gives `HashMap` and `HashSet` a reproducible iteration order, but is not resistant to HashDoS
+#[derive(Debug, Clone, Copy)]
+pub struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    #[inline]
+    fn default() -> Self {
+        FnvHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+/// `BuildHasher` of [FnvHasher], use with `map! { hasher = FnvBuildHasher; ... }`
+pub type FnvBuildHasher = BuildHasherDefault<FnvHasher>;
+
+/// A key that appeared twice in [try_map](macro.try_map.html) or [try_set](macro.try_set.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKey<K> {
+    /// The repeated key
+    pub key: K,
+    /// Position of the entry that repeated it
+    pub index: usize,
+}
+
+impl<K: Debug> Display for DuplicateKey<K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate key {:?} at entry {}", self.key, self.index)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Debug> std::error::Error for DuplicateKey<K> {}
+
+/// An iterator that ended before filling `arr![try in iter; N]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooShort {
+    /// Length of the array
+    pub expected: usize,
+    /// Items the iterator actually yielded
+    pub found: usize,
+}
+
+impl Display for TooShort {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "iterator yielded {} items but the array needs {}",
+            self.found, self.expected
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TooShort {}
+
+/// Max-heap ordered by a key extracted from each element, built by `heap![by f; ...]`  
+/// keys are computed once on push, wrap them in `Reverse` for a min-heap
+pub struct KeyedHeap<T, K, F> {
+    heap: _alloc::BinaryHeap<Keyed<K, T>>,
+    key: F,
+}
+
+struct Keyed<K, T> {
+    key: K,
+    value: T,
+}
+
+impl<K: Ord, T> PartialEq for Keyed<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Ord, T> Eq for Keyed<K, T> {}
+
+impl<K: Ord, T> PartialOrd for Keyed<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, T> Ord for Keyed<K, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> KeyedHeap<T, K, F> {
+    /// Empty heap ordered by `key`
+    pub fn new(key: F) -> Self {
+        Self {
+            heap: _alloc::BinaryHeap::new(),
+            key,
+        }
+    }
+
+    /// Heap of `values` ordered by `key`
+    pub fn from_vec(values: _alloc::Vec<T>, key: F) -> Self {
+        let heap = values
+            .into_iter()
+            .map(|value| Keyed {
+                key: key(&value),
+                value,
+            })
+            .collect();
+        Self { heap, key }
+    }
+
+    /// Pushes `value`, computing its key
+    pub fn push(&mut self, value: T) {
+        let key = (self.key)(&value);
+        self.heap.push(Keyed { key, value });
+    }
+
+    /// Removes the element with the greatest key
+    pub fn pop(&mut self) -> Option<T> {
+        self.heap.pop().map(|e| e.value)
+    }
+
+    /// The element with the greatest key
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek().map(|e| &e.value)
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Whether the heap has no elements
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Elements in arbitrary order
+    pub fn into_vec(self) -> _alloc::Vec<T> {
+        self.heap.into_iter().map(|e| e.value).collect()
+    }
+
+    /// Elements ordered by ascending key
+    pub fn into_sorted_vec(self) -> _alloc::Vec<T> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|e| e.value)
+            .collect()
+    }
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> Extend<T> for KeyedHeap<T, K, F> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let key = &self.key;
+        self.heap.extend(iter.into_iter().map(|value| Keyed {
+            key: key(&value),
+            value,
+        }));
+    }
+}
+
+impl<T: Debug, K, F> Debug for KeyedHeap<T, K, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.heap.iter().map(|e| &e.value))
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _hash_new {
+    ($t:ident, $($ctor:tt)*) => { $crate::_alloc::$t::$($ctor)* };
+}
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _hash_new {
+    (HashMap, $($ctor:tt)*) => {
+        ::core::compile_error!("`map!` builds a `HashMap` which needs the `std` feature of libsugar, use `btmap!` or pass a map like `hashbrown::HashMap::new()` with `map! { let m; ... }`")
+    };
+    (HashSet, $($ctor:tt)*) => {
+        ::core::compile_error!("`set!` builds a `HashSet` which needs the `std` feature of libsugar, use `btset!` or pass a set like `hashbrown::HashSet::new()` with `set![let s; ...]`")
+    };
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _sync_new {
+    ($t:ident, $e:expr) => {
+        $crate::_alloc::Arc::new(::std::sync::$t::new($e))
+    };
+}
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _sync_new {
+    ($t:ident, $e:expr) => {
+        ::core::compile_error!("`new!(arc_mutex x)` and `new!(arc_rwlock x)` need the `std` feature of libsugar, `Mutex` and `RwLock` live in `std::sync`")
+    };
+}
+
+#[cfg(feature = "new-uninit")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _new_uninit {
+    ($t:ty) => {
+        $crate::_alloc::Box::<$t>::new_uninit()
+    };
+}
+#[cfg(not(feature = "new-uninit"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _new_uninit {
+    ($t:ty) => {
+        ::core::compile_error!(
+            "`new!(uninit T)` needs the `new-uninit` feature of libsugar, which needs Rust 1.82"
+        )
+    };
+}
+
+#[cfg(feature = "new-zeroed")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _new_zeroed {
+    ($t:ty) => {
+        $crate::_alloc::Box::<$t>::new_zeroed()
+    };
+}
+#[cfg(not(feature = "new-zeroed"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _new_zeroed {
+    ($t:ty) => {
+        ::core::compile_error!(
+            "`new!(zeroed T)` needs the `new-zeroed` feature of libsugar, which needs Rust 1.92"
+        )
+    };
+}
+
 /// new a `Box<T>`  
 /// ```
 /// # use libsugar::*;
@@ -11,13 +760,112 @@
 /// Box::new(xxx)
 /// # ;
 /// ```
+/// ---
+/// shared and pinned  
+/// named like the methods of [IntoRc](named_into/trait.IntoRc.html), [IntoArc](named_into/trait.IntoArc.html) and [IntoPin](named_into/trait.IntoPin.html)
+/// ```
+/// # use libsugar::*;
+/// # let x = 1;
+/// let a = new!(rc x);          // Rc::new(x)
+/// let b = new!(rc_refcell x);  // Rc::new(RefCell::new(x))
+/// let c = new!(rc_cell x);     // Rc::new(Cell::new(x))
+/// let d = new!(arc x);         // Arc::new(x)
+/// let e = new!(arc_mutex x);   // Arc::new(Mutex::new(x)), needs the `"std"` feature
+/// let f = new!(arc_rwlock x);  // Arc::new(RwLock::new(x)), needs the `"std"` feature
+/// let g = new!(pin x);         // Box::pin(x)
+/// let h = new!(pin_arc x);     // Arc::pin(x)
+/// ```
+/// ---
+/// cyclic  
+/// the closure gets the `Weak` of the value it builds
+/// ```
+/// # use libsugar::*;
+/// use std::rc::Weak;
+/// struct Node { me: Weak<Node> }
+/// let n = new!(rc_cycle |me: &Weak<Node>| Node { me: me.clone() });
+/// assert!(n.me.upgrade().is_some());
+/// ```
+/// *equivalent to*
+/// ```
+/// # use std::rc::{Rc, Weak};
+/// # struct Node { me: Weak<Node> }
+/// let n = Rc::new_cyclic(|me: &Weak<Node>| Node { me: me.clone() });
+/// # assert!(n.me.upgrade().is_some());
+/// ```
+/// ---
+/// uninitialised  
+/// allocates on the heap without building the value on the stack first  
+/// `uninit T` gives a `Box<MaybeUninit<T>>`, it needs the `"new-uninit"` feature and Rust 1.82
+#[cfg_attr(feature = "new-uninit", doc = "```")]
+#[cfg_attr(not(feature = "new-uninit"), doc = "```ignore")]
+/// # use libsugar::*;
+/// let slot = new!(uninit [u64; 4]);
+/// let slot = Box::write(slot, [1; 4]);
+/// assert_eq!(slot[3], 1);
+/// ```
+/// *equivalent to*
+#[cfg_attr(feature = "new-uninit", doc = "```")]
+#[cfg_attr(not(feature = "new-uninit"), doc = "```ignore")]
+/// let slot = Box::<[u64; 4]>::new_uninit();
+/// # let _ = slot;
+/// ```
+/// `zeroed T` gives a `Box<T>` of zero bytes, which is unsafe because zero may not be a valid `T`,
+/// it needs the `"new-zeroed"` feature and Rust 1.92
+#[cfg_attr(feature = "new-zeroed", doc = "```")]
+#[cfg_attr(not(feature = "new-zeroed"), doc = "```ignore")]
+/// # use libsugar::*;
+/// let buf: Box<[u8; 1 << 20]> = unsafe { new!(zeroed [u8; 1 << 20]) };
+/// assert_eq!(buf[1000], 0);
+/// ```
+/// *equivalent to*
+#[cfg_attr(feature = "new-zeroed", doc = "```")]
+#[cfg_attr(not(feature = "new-zeroed"), doc = "```ignore")]
+/// let buf: Box<[u8; 1 << 20]> = unsafe { Box::<[u8; 1 << 20]>::new_zeroed().assume_init() };
+/// # assert_eq!(buf[1000], 0);
+/// ```
 #[macro_export]
 macro_rules! new {
     () => {
-        Box::new(Default::default())
+        $crate::_alloc::Box::new(::core::default::Default::default())
+    };
+    (rc $e:expr) => {
+        $crate::_alloc::Rc::new($e)
+    };
+    (rc_refcell $e:expr) => {
+        $crate::_alloc::Rc::new(::core::cell::RefCell::new($e))
+    };
+    (rc_cell $e:expr) => {
+        $crate::_alloc::Rc::new(::core::cell::Cell::new($e))
+    };
+    (rc_cycle $f:expr) => {
+        $crate::_alloc::Rc::new_cyclic($f)
+    };
+    (arc $e:expr) => {
+        $crate::_alloc::Arc::new($e)
+    };
+    (arc_mutex $e:expr) => {
+        $crate::_sync_new!(Mutex, $e)
+    };
+    (arc_rwlock $e:expr) => {
+        $crate::_sync_new!(RwLock, $e)
+    };
+    (arc_cycle $f:expr) => {
+        $crate::_alloc::Arc::new_cyclic($f)
+    };
+    (pin $e:expr) => {
+        $crate::_alloc::Box::pin($e)
+    };
+    (pin_arc $e:expr) => {
+        $crate::_alloc::Arc::pin($e)
+    };
+    (zeroed $t:ty) => {
+        $crate::_new_zeroed!($t).assume_init()
+    };
+    (uninit $t:ty) => {
+        $crate::_new_uninit!($t)
     };
     ($e:expr) => {
-        Box::new($e)
+        $crate::_alloc::Box::new($e)
     };
 }
 
@@ -34,12 +882,104 @@ macro_rules! new {
 /// vec![a, b, c].into_boxed_slice()
 /// # ;
 /// ```
+/// ---
+/// spread  
+/// `..iterable` entries are merged in place, the same works in every collection macro
+/// ```
+/// # use libsugar::*;
+/// let head = [1, 2];
+/// let tail = vec![4, 5];
+/// let a = arr![..head, 3, ..tail];
+/// assert_eq!(&*a, &[1, 2, 3, 4, 5]);
+/// ```
+/// ---
+/// conditional  
+/// `x if cond` is only added when `cond` holds, the same works in every collection macro  
+/// the entry is split at its first `if`, so a value that contains an `if` expression must be parenthesised
+/// ```
+/// # use libsugar::*;
+/// # let verbose = true;
+/// let args = arr!["run", "-v" if verbose, "--release" if !verbose];
+/// assert_eq!(&*args, &["run", "-v"]);
+/// let n = arr![(1 + if verbose { 1 } else { 2 }) if verbose];
+/// assert_eq!(&*n, &[2]);
+/// ```
+/// ```compile_fail
+/// # use libsugar::*;
+/// # let verbose = true;
+/// let n = arr![1 + if verbose { 1 } else { 2 } if verbose];
+/// ```
+/// ---
+/// repeat
+/// ```
+/// # use libsugar::*;
+/// let a = arr![String::from("a"); 3];
+/// assert_eq!(a.len(), 3);
+/// ```
+/// *equivalent to*
+/// ```
+/// let a = vec![String::from("a"); 3].into_boxed_slice();
+/// # assert_eq!(a.len(), 3);
+/// ```
+/// ---
+/// fixed-size  
+/// `fn f; N` calls `f` with every index, `in iter; N` takes the first `N` items and panics if there are fewer,
+/// `try in iter; N` returns `Err(TooShort)` instead, none of them need `Copy` or `Default`
+/// ```
+/// # use libsugar::*;
+/// let a: [String; 3] = arr![fn |i| i.to_string(); 3];
+/// assert_eq!(a, ["0", "1", "2"]);
+/// let b = arr![in "a b c d".split(' '); 3];
+/// assert_eq!(b, ["a", "b", "c"]);
+/// let c = arr![try in vec![1, 2]; 3];
+/// assert_eq!(c, Err(TooShort { expected: 3, found: 2 }));
+/// ```
+/// *equivalent to*
+/// ```
+/// let a: [String; 3] = core::array::from_fn(|i| i.to_string());
+/// # assert_eq!(a, ["0", "1", "2"]);
+/// let mut it = "a b c d".split(' ');
+/// let b: [_; 3] = core::array::from_fn(|_| it.next().unwrap());
+/// # assert_eq!(b, ["a", "b", "c"]);
+/// ```
 #[macro_export]
 macro_rules! arr {
-    [ $($e:expr),* $(,)? ] => { vec![$($e),*].into_boxed_slice() };
+    [ fn $f:expr; $n:expr ] => {{
+        let a: [_; $n] = ::core::array::from_fn($f);
+        a
+    }};
+    [ in $it:expr; $n:expr ] => {{
+        let mut it = ::core::iter::IntoIterator::into_iter($it);
+        let a: [_; $n] = ::core::array::from_fn(|_| match ::core::iter::Iterator::next(&mut it) {
+            ::core::option::Option::Some(v) => v,
+            ::core::option::Option::None => ::core::panic!("iterator is shorter than the array length {}", $n),
+        });
+        a
+    }};
+    [ try in $it:expr; $n:expr ] => {{
+        let mut it = ::core::iter::Iterator::fuse(::core::iter::IntoIterator::into_iter($it));
+        let a: [_; $n] = ::core::array::from_fn(|_| ::core::iter::Iterator::next(&mut it));
+        let found = a.iter().filter(|v| v.is_some()).count();
+        if found == $n {
+            ::core::result::Result::Ok(a.map(|v| match v {
+                ::core::option::Option::Some(v) => v,
+                ::core::option::Option::None => ::core::unreachable!(),
+            }))
+        } else {
+            ::core::result::Result::Err($crate::TooShort { expected: $n, found })
+        }
+    }};
+    [ $elem:expr; $n:expr ] => { $crate::_alloc::vec![$elem; $n].into_boxed_slice() };
+    [ $($e:tt),* $(,)? ] => { $crate::_alloc::vec![$($e),*].into_boxed_slice() };
+    [ $($t:tt)* ] => {{
+        let mut v = $crate::_alloc::Vec::new();
+        $crate::_spread!(@push v push [] $($t)*);
+        v.into_boxed_slice()
+    }};
 }
 
-/// new a `VecDeque<T>`
+/// new a `VecDeque<T>`  
+/// also takes `..iterable` spreads like [arr](macro.arr.html)
 /// ```
 /// # use libsugar::*;
 /// # use std::collections::*;
@@ -56,12 +996,18 @@ macro_rules! arr {
 /// ```
 #[macro_export]
 macro_rules! deque {
-    [] => { std::collections::VecDeque::new() };
-    [ $elem:expr; $n:expr ] => { std::collections::VecDeque::from(vec![$elem; $n]) };
-    [ $($e:expr),* $(,)? ] => { std::collections::VecDeque::from(vec![$($e),+]) };
+    [] => { $crate::_alloc::VecDeque::new() };
+    [ $elem:expr; $n:expr ] => { $crate::_alloc::VecDeque::from($crate::_alloc::vec![$elem; $n]) };
+    [ $($e:tt),* $(,)? ] => { $crate::_alloc::VecDeque::from($crate::_alloc::vec![$($e),*]) };
+    [ $($t:tt)* ] => {{
+        let mut d = $crate::_alloc::VecDeque::new();
+        $crate::_spread!(@push d push_back [] $($t)*);
+        d
+    }};
 }
 
 /// new a `LinkedList<T>`  
+/// also takes `..iterable` spreads like [arr](macro.arr.html)
 ///
 /// ----------  
 /// ### Push Back  
@@ -136,22 +1082,27 @@ macro_rules! deque {
 /// ```
 #[macro_export]
 macro_rules! list {
-    [] => { std::collections::LinkedList::new() };
+    [] => { $crate::_alloc::LinkedList::new() };
     [ $elem:expr; $n:expr ] => {{
-        let mut l = std::collections::LinkedList::new();
+        let mut l = $crate::_alloc::LinkedList::new();
         for _ in 0..$n {
             l.push_back($elem);
         }
         l
     }};
-    [ $($e:expr),* $(,)? ] => {{
-        let mut l = std::collections::LinkedList::new();
+    [ $($e:tt),* $(,)? ] => {{
+        let mut l = $crate::_alloc::LinkedList::new();
         $( l.push_back($e); )*
         l
     }};
-    [ <- $($e:expr),* $(,)? ] => {{
-        let mut l = std::collections::LinkedList::new();
-        $( l.push_front($e); )*
+    [ <- $($t:tt)* ] => {{
+        let mut l = $crate::_alloc::LinkedList::new();
+        $crate::_spread!(@push l push_front [] $($t)*);
+        l
+    }};
+    [ $($t:tt)* ] => {{
+        let mut l = $crate::_alloc::LinkedList::new();
+        $crate::_spread!(@push l push_back [] $($t)*);
         l
     }};
 }
@@ -172,7 +1123,7 @@ macro_rules! list {
 /// # use std::collections::*;
 /// # let ka = 1; let va = 2; let kb = 3; let vb = 4;
 /// {
-///     let mut m = HashMap::new();
+///     let mut m = HashMap::with_capacity(2);
 ///     m.insert(ka, va);
 ///     m.insert(kb, vb);
 ///     m
@@ -180,12 +1131,107 @@ macro_rules! list {
 /// # ;
 /// ```
 /// ---
-/// map like
+/// spread  
+/// `..iterable` of pairs merged in order, later keys override earlier ones
 /// ```
 /// # use libsugar::*;
-/// # use std::collections::*;
-/// # let ka = 1; let va = 2; let kb = 3; let vb = 4;
-/// map! { let BTreeMap::new();
+/// let defaults = map! { "host" => "localhost", "port" => "80" };
+/// let m = map! { ..defaults, "port" => "8080" };
+/// assert_eq!(m["port"], "8080");
+/// ```
+/// *equivalent to*
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// # let defaults = map! { "host" => "localhost", "port" => "80" };
+/// let m = {
+///     let mut m = HashMap::new();
+///     let it = defaults.into_iter();
+///     m.reserve(it.size_hint().0);
+///     for (k, v) in it {
+///         m.insert(k, v);
+///     }
+///     m.reserve(1);
+///     m.insert("port", "8080");
+///     m
+/// };
+/// # assert_eq!(m["port"], "8080");
+/// ```
+/// ---
+/// conditional  
+/// `k => v if cond` is inserted only when `cond` holds, `k =>? opt` only when `opt` is `Some`  
+/// like in [arr](macro.arr.html) a value that contains an `if` expression must be parenthesised
+/// ```
+/// # use libsugar::*;
+/// # let debug = false; let user: Option<&str> = Some("root");
+/// let m = map! {
+///     "host" => "localhost",
+///     "log" => "trace" if debug,
+///     "user" =>? user,
+/// };
+/// # assert_eq!(m.len(), 2);
+/// ```
+/// *equivalent to*
+/// ```
+/// # use std::collections::*;
+/// # let debug = false; let user: Option<&str> = Some("root");
+/// let m = {
+///     let mut m = HashMap::new();
+///     m.reserve(1);
+///     m.insert("host", "localhost");
+///     if debug {
+///         m.insert("log", "trace");
+///     }
+///     if let Some(v) = user {
+///         m.insert("user", v);
+///     }
+///     m
+/// };
+/// # assert_eq!(m.len(), 2);
+/// ```
+/// ---
+/// strict  
+/// panics on a duplicate key, also `map! { strict let m; ... }` and `btmap! { strict; ... }`, see [try_map](macro.try_map.html)
+/// ```should_panic
+/// # use libsugar::*;
+/// map! { strict;
+///     1 => "a",
+///     1 => "b",
+/// }
+/// # ;
+/// ```
+/// ---
+/// hasher  
+/// any `BuildHasher + Default`, also `map! { strict hasher = H; ... }`
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// # let ka = 1; let va = 2; let kb = 3; let vb = 4;
+/// let m: HashMap<_, _, FnvBuildHasher> = map! { hasher = FnvBuildHasher;
+///     ka => va,
+///     kb => vb,
+/// };
+/// ```
+/// *equivalent to*
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// # let ka = 1; let va = 2; let kb = 3; let vb = 4;
+/// let m: HashMap<_, _, FnvBuildHasher> = {
+///     let mut m = HashMap::with_capacity_and_hasher(2, FnvBuildHasher::default());
+///     m.insert(ka, va);
+///     m.insert(kb, vb);
+///     m
+/// };
+/// ```
+/// ---
+/// map like  
+/// the entry count is reserved first if the map is a `HashMap`
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// # let ka = 1; let va = 2; let kb = 3; let vb = 4;
+/// map! { let BTreeMap::new();
 ///     ka => va,
 ///     kb => vb,
 /// }
@@ -205,9 +1251,18 @@ macro_rules! list {
 /// ```
 #[macro_export]
 macro_rules! map {
-    { } => { std::collections::HashMap::new() };
+    { } => { $crate::_hash_new!(HashMap, new()) };
+    { strict; $($k:expr => $v:expr),* $(,)? } => { $crate::_strict!($crate::try_map!{ $($k => $v),* }) };
+    { strict let $m:expr; $($k:expr => $v:expr),* $(,)? } => { $crate::_strict!($crate::try_map!{ let $m; $($k => $v),* }) };
+    { strict hasher = $h:ty; $($k:expr => $v:expr),* $(,)? } => { $crate::_strict!($crate::try_map!{ hasher = $h; $($k => $v),* }) };
+    { hasher = $h:ty; $($k:expr => $v:expr),* $(,)? } => {
+        $crate::map!{ let $crate::_hash_new!(HashMap, with_capacity_and_hasher($crate::_count!($($k),*), <$h as ::core::default::Default>::default())); $($k => $v),* }
+    };
+    { hasher = $h:ty; $($t:tt)* } => {
+        $crate::map!{ let $crate::_hash_new!(HashMap, with_hasher(<$h as ::core::default::Default>::default())); $($t)* }
+    };
     { $($k:expr => $v:expr),* $(,)? } => {{
-        let mut m = std::collections::HashMap::new();
+        let mut m = $crate::_hash_new!(HashMap, with_capacity($crate::_count!($($k),*)));
         $(
             m.insert($k, $v);
         )*
@@ -215,14 +1270,26 @@ macro_rules! map {
     }};
     { let $m:expr; $($k:expr => $v:expr),* $(,)? } => {{
         let mut m = $m;
+        $crate::_reserve!(m, $crate::_count!($($k),*));
         $(
             m.insert($k, $v);
         )*
         m
     }};
+    { let $m:expr; $($t:tt)* } => {{
+        let mut m = $m;
+        $crate::_spread!(@pair m insert [] $($t)*);
+        m
+    }};
+    { $($t:tt)* } => {{
+        let mut m = $crate::_hash_new!(HashMap, new());
+        $crate::_spread!(@pair m insert [] $($t)*);
+        m
+    }};
 }
 
 /// new a `BTreeMap<K, V>`  
+/// also takes `..iterable` spreads like [map](macro.map.html)
 /// ```
 /// # use libsugar::*;
 /// # use std::collections::*;
@@ -247,14 +1314,81 @@ macro_rules! map {
 /// ```
 #[macro_export]
 macro_rules! btmap {
-    { } => { std::collections::BTreeMap::new() };
+    { } => { $crate::_alloc::BTreeMap::new() };
+    { strict; $($k:expr => $v:expr),* $(,)? } => { $crate::_strict!($crate::try_map!{ let $crate::_alloc::BTreeMap::new(); $($k => $v),* }) };
     { $($k:expr => $v:expr),* $(,)? } => {{
-        let mut m = std::collections::BTreeMap::new();
+        let mut m = $crate::_alloc::BTreeMap::new();
         $(
             m.insert($k, $v);
         )*
         m
     }};
+    { $($t:tt)* } => {{
+        let mut m = $crate::_alloc::BTreeMap::new();
+        $crate::_spread!(@pair m insert [] $($t)*);
+        m
+    }};
+}
+
+/// new a `HashMap<K, V>`, or fail on the first duplicate key  
+/// also `try_map! { hasher = H; ... }` like [map](macro.map.html)
+/// ```
+/// # use libsugar::*;
+/// let m = try_map! {
+///     1 => "a",
+///     2 => "b",
+///     1 => "c",
+/// };
+/// assert_eq!(m, Err(DuplicateKey { key: 1, index: 2 }));
+/// ```
+/// *equivalent to*
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// # let m: Result<HashMap<i32, &str>, DuplicateKey<i32>> =
+/// {
+///     let mut m = HashMap::with_capacity(3);
+///     IntoIterator::into_iter([(1, "a"), (2, "b"), (1, "c")]).enumerate().try_for_each(|(index, (key, v))| {
+///         if m.contains_key(&key) {
+///             return Err(DuplicateKey { key, index });
+///         }
+///         m.insert(key, v);
+///         Ok(())
+///     }).map(|_| m)
+/// }
+/// # ;
+/// ```
+/// ---
+/// map like
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// let m = try_map! { let BTreeMap::new();
+///     1 => "a",
+///     2 => "b",
+/// };
+/// assert_eq!(m.unwrap().len(), 2);
+/// ```
+#[macro_export]
+macro_rules! try_map {
+    { } => { ::core::result::Result::<_, $crate::DuplicateKey<_>>::Ok($crate::_hash_new!(HashMap, new())) };
+    { hasher = $h:ty; $($k:expr => $v:expr),* $(,)? } => {
+        $crate::try_map!{ let $crate::_hash_new!(HashMap, with_capacity_and_hasher($crate::_count!($($k),*), <$h as ::core::default::Default>::default())); $($k => $v),* }
+    };
+    { $($k:expr => $v:expr),* $(,)? } => {
+        $crate::try_map!{ let $crate::_hash_new!(HashMap, with_capacity($crate::_count!($($k),*))); $($k => $v),* }
+    };
+    { let $m:expr; $($k:expr => $v:expr),* $(,)? } => {{
+        let mut m = $m;
+        $crate::_reserve!(m, $crate::_count!($($k),*));
+        ::core::iter::IntoIterator::into_iter([$(($k, $v)),*]).enumerate().try_for_each(|(index, (key, v))| {
+            if m.contains_key(&key) {
+                return ::core::result::Result::Err($crate::DuplicateKey { key, index });
+            }
+            m.insert(key, v);
+            ::core::result::Result::Ok(())
+        }).map(|_| m)
+    }};
 }
 
 /// append items to a map  
@@ -278,8 +1412,103 @@ macro_rules! btmap {
 /// m.insert(kb, vb);
 /// # ;
 /// ```
+/// ---
+/// replaced  
+/// returns the displaced `(key, old)` pairs in a `Vec`
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// let mut m = btmap! { "a" => 1 };
+/// let old = map_append! { m; replaced; "a" => 2, "b" => 3 };
+/// assert_eq!(old, [("a", 1)]);
+/// ```
+/// ---
+/// merge  
+/// combines the old and new value of a repeated key
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// let mut counts = btmap! { "a" => 1 };
+/// map_append! { counts; merge |old, new| old + new; "a" => 1, "b" => 1, "a" => 1 };
+/// assert_eq!(counts["a"], 3);
+/// ```
+/// *equivalent to*
+/// ```
+/// # use std::collections::*;
+/// # let mut counts = BTreeMap::new(); counts.insert("a", 1);
+/// let v = match counts.remove("a") {
+///     Some(old) => old + 1,
+///     None => 1,
+/// };
+/// counts.insert("a", v);
+/// // ...
+/// ```
+/// ---
+/// keep  
+/// only inserts missing keys, the value of an existing key is not evaluated
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// let mut m = btmap! { "a" => 1 };
+/// map_append! { m; keep; "a" => unreachable!(), "b" => 2 };
+/// assert_eq!(m, btmap! { "a" => 1, "b" => 2 });
+/// ```
+/// ---
+/// multi  
+/// appends to the inner containers of a [multimap](macro.multimap.html)
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// let mut m: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+/// map_append! { multi m;
+///     "a" => 1,
+///     "a" => 2,
+///     "b" => 3,
+/// }
+/// assert_eq!(m["a"].len(), 2);
+/// ```
 #[macro_export]
 macro_rules! map_append {
+    { multi $m:expr; $($t:tt)* } => {{
+        #[allow(unused_imports)]
+        use $crate::_multi::_MultiMap;
+        let mut m = &mut $m;
+        $crate::_spread!(@pair m _multi_insert [] $($t)*);
+    }};
+    { $m:expr; replaced; $($k:expr => $v:expr),* $(,)? } => {{
+        let m = &mut $m;
+        let mut replaced = $crate::_alloc::Vec::new();
+        $({
+            let k = $k;
+            if let ::core::option::Option::Some(kv) = m.remove_entry(&k) {
+                replaced.push(kv);
+            }
+            m.insert(k, $v);
+        })*
+        replaced
+    }};
+    { $m:expr; merge $f:expr; $($k:expr => $v:expr),* $(,)? } => {{
+        let m = &mut $m;
+        let f = $f;
+        $({
+            let k = $k;
+            let v = $v;
+            let v = match m.remove(&k) {
+                ::core::option::Option::Some(old) => f(old, v),
+                ::core::option::Option::None => v,
+            };
+            m.insert(k, v);
+        })*
+    }};
+    { $m:expr; keep; $($k:expr => $v:expr),* $(,)? } => {{
+        let m = &mut $m;
+        $({
+            let k = $k;
+            if !m.contains_key(&k) {
+                m.insert(k, $v);
+            }
+        })*
+    }};
     { $m:expr; $($k:expr => $v:expr),* $(,)? } => {
         $(
             $m.insert($k, $v);
@@ -287,7 +1516,69 @@ macro_rules! map_append {
     };
 }
 
+/// new a `HashMap<K, Vec<V>>` where repeated keys append instead of overwriting  
+/// also takes `..iterable` spreads of pairs like [map](macro.map.html)
+/// ```
+/// # use libsugar::*;
+/// let m = multimap! {
+///     "accept" => "text/html",
+///     "accept" => "application/json",
+///     "host" => "example.com",
+/// };
+/// assert_eq!(m["accept"], ["text/html", "application/json"]);
+/// ```
+/// *equivalent to*
+/// ```
+/// # use std::collections::*;
+/// let mut m: HashMap<_, Vec<_>> = HashMap::new();
+/// m.entry("accept").or_default().push("text/html");
+/// m.entry("accept").or_default().push("application/json");
+/// m.entry("host").or_default().push("example.com");
+/// # assert_eq!(m["accept"], ["text/html", "application/json"]);
+/// ```
+/// ---
+/// inner container  
+/// `Vec`, `VecDeque`, `BTreeSet` or `HashSet` before the entries, and `let m;` for another map
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// let m = multimap! { HashSet; 1 => 2, 1 => 2, 1 => 3 };
+/// assert_eq!(m[&1].len(), 2);
+/// let g = multimap! { let BTreeMap::new(); BTreeSet; 'a' => 'b', 'a' => 'c', 'b' => 'c' };
+/// assert_eq!(g[&'a'], btset!['b', 'c']);
+/// ```
+#[macro_export]
+macro_rules! multimap {
+    { let $m:expr; $inner:ident; $($t:tt)* } => {{
+        let mut m = $m;
+        $crate::_multi::_inner::<_, _, _, $crate::_multi_inner!($inner)>(&m);
+        $crate::map_append! { multi m; $($t)* }
+        m
+    }};
+    { let $m:expr; $($t:tt)* } => {{
+        let mut m = $m;
+        $crate::map_append! { multi m; $($t)* }
+        m
+    }};
+    { $inner:ident; $($t:tt)* } => {
+        $crate::multimap! { let $crate::_hash_new!(HashMap, new()); $inner; $($t)* }
+    };
+    { $($t:tt)* } => {
+        $crate::multimap! { let $crate::_hash_new!(HashMap, new()); Vec; $($t)* }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _multi_inner {
+    (Vec) => { $crate::_alloc::Vec<_> };
+    (VecDeque) => { $crate::_alloc::VecDeque<_> };
+    (BTreeSet) => { $crate::_alloc::BTreeSet<_> };
+    (HashSet) => { $crate::_alloc::HashSet<_> };
+}
+
 /// new a `HashSet<V>`  
+/// also takes `..iterable` spreads like [arr](macro.arr.html)
 /// ```
 /// # use libsugar::*;
 /// # use std::collections::*;
@@ -300,7 +1591,7 @@ macro_rules! map_append {
 /// # use std::collections::*;
 /// # let a = 1; let b = 2;
 /// {
-///     let mut s = HashSet::new();
+///     let mut s = HashSet::with_capacity(2);
 ///     s.insert(a);
 ///     s.insert(b);
 ///     s
@@ -308,7 +1599,24 @@ macro_rules! map_append {
 /// # ;
 /// ```
 /// ---
-/// set like
+/// strict  
+/// panics on a duplicate item, also `set![strict let s; ...]` and `btset![strict; ...]`, see [try_set](macro.try_set.html)
+/// ```should_panic
+/// # use libsugar::*;
+/// set![strict; 1, 2, 1]
+/// # ;
+/// ```
+/// ---
+/// hasher  
+/// any `BuildHasher + Default`, also `set![strict hasher = H; ...]`
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// let s: HashSet<_, FnvBuildHasher> = set![hasher = FnvBuildHasher; 1, 2];
+/// ```
+/// ---
+/// set like  
+/// the entry count is reserved first if the set is a `HashSet`
 /// ```
 /// # use libsugar::*;
 /// # use std::collections::*;
@@ -330,24 +1638,69 @@ macro_rules! map_append {
 /// ```
 #[macro_export]
 macro_rules! set {
-    { } => { std::collections::HashSet::new() };
-    { $($e:expr),* $(,)? } => {{
-        let mut s = std::collections::HashSet::new();
+    { } => { $crate::_hash_new!(HashSet, new()) };
+    { strict; $($e:expr),* $(,)? } => { $crate::_strict!($crate::try_set![$($e),*]) };
+    { strict let $s:expr; $($e:expr),* $(,)? } => { $crate::_strict!($crate::try_set![let $s; $($e),*]) };
+    { strict hasher = $h:ty; $($e:expr),* $(,)? } => { $crate::_strict!($crate::try_set![hasher = $h; $($e),*]) };
+    { hasher = $h:ty; $($e:tt),* $(,)? } => {
+        $crate::set![let $crate::_hash_new!(HashSet, with_capacity_and_hasher($crate::_count!($($e),*), <$h as ::core::default::Default>::default())); $($e),*]
+    };
+    { hasher = $h:ty; $($t:tt)* } => {
+        $crate::set![let $crate::_hash_new!(HashSet, with_hasher(<$h as ::core::default::Default>::default())); $($t)*]
+    };
+    { $($e:tt),* $(,)? } => {{
+        let mut s = $crate::_hash_new!(HashSet, with_capacity($crate::_count!($($e),*)));
         $(
             s.insert($e);
         )*
         s
     }};
-    { let $s:expr; $($e:expr),* $(,)? } => {{
+    { let $s:expr; $($t:tt)* } => {{
         let mut s = $s;
-        $(
-            s.insert($e);
-        )*
+        $crate::_spread!(@push s insert [] $($t)*);
+        s
+    }};
+    { $($t:tt)* } => {{
+        let mut s = $crate::_hash_new!(HashSet, new());
+        $crate::_spread!(@push s insert [] $($t)*);
         s
     }};
 }
 
+/// new a `HashSet<V>`, or fail on the first duplicate item  
+/// also `try_set![hasher = H; ...]` like [set](macro.set.html)
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// let s = try_set![1, 2, 1];
+/// assert_eq!(s, Err(DuplicateKey { key: 1, index: 2 }));
+/// let s = try_set![let BTreeSet::new(); 1, 2];
+/// assert_eq!(s.unwrap().len(), 2);
+/// ```
+#[macro_export]
+macro_rules! try_set {
+    { } => { ::core::result::Result::<_, $crate::DuplicateKey<_>>::Ok($crate::_hash_new!(HashSet, new())) };
+    { hasher = $h:ty; $($e:expr),* $(,)? } => {
+        $crate::try_set![let $crate::_hash_new!(HashSet, with_capacity_and_hasher($crate::_count!($($e),*), <$h as ::core::default::Default>::default())); $($e),*]
+    };
+    { $($e:expr),* $(,)? } => {
+        $crate::try_set![let $crate::_hash_new!(HashSet, with_capacity($crate::_count!($($e),*))); $($e),*]
+    };
+    { let $s:expr; $($e:expr),* $(,)? } => {{
+        let mut s = $s;
+        $crate::_reserve!(s, $crate::_count!($($e),*));
+        ::core::iter::IntoIterator::into_iter([$($e),*]).enumerate().try_for_each(|(index, key)| {
+            if s.contains(&key) {
+                return ::core::result::Result::Err($crate::DuplicateKey { key, index });
+            }
+            s.insert(key);
+            ::core::result::Result::Ok(())
+        }).map(|_| s)
+    }};
+}
+
 /// new a `BTreeSet<V>`  
+/// also takes `..iterable` spreads like [arr](macro.arr.html)
 /// ```
 /// # use libsugar::*;
 /// # use std::collections::*;
@@ -369,17 +1722,24 @@ macro_rules! set {
 /// ```
 #[macro_export]
 macro_rules! btset {
-    { } => { std::collections::BTreeSet::new() };
-    { $($e:expr),* $(,)? } => {{
-        let mut s = std::collections::BTreeSet::new();
+    { } => { $crate::_alloc::BTreeSet::new() };
+    { strict; $($e:expr),* $(,)? } => { $crate::_strict!($crate::try_set![let $crate::_alloc::BTreeSet::new(); $($e),*]) };
+    { $($e:tt),* $(,)? } => {{
+        let mut s = $crate::_alloc::BTreeSet::new();
         $(
             s.insert($e);
         )*
         s
     }};
+    { $($t:tt)* } => {{
+        let mut s = $crate::_alloc::BTreeSet::new();
+        $crate::_spread!(@push s insert [] $($t)*);
+        s
+    }};
 }
 
 /// new a `BinaryHeap<V>`  
+/// also takes `..iterable` spreads like [arr](macro.arr.html)
 /// ```
 /// # use libsugar::*;
 /// # use std::collections::*;
@@ -394,9 +1754,440 @@ macro_rules! btset {
 /// BinaryHeap::from(vec![a, b])
 /// # ;
 /// ```
+/// ---
+/// min-heap  
+/// elements are wrapped in `Reverse`
+/// ```
+/// # use libsugar::*;
+/// # use core::cmp::Reverse;
+/// let mut h = heap![min; 3, 1, 2];
+/// assert_eq!(h.pop(), Some(Reverse(1)));
+/// ```
+/// a single entry like `heap![min; n]` stays a repeat of a variable `min` as in `vec!`,
+/// use `@min` for a min-heap of one element, it is accepted for any number of elements
+/// ```
+/// # use libsugar::*;
+/// # use core::cmp::Reverse;
+/// let min = 0;
+/// let r = heap![min; 2];
+/// let h = heap![@min; 2];
+/// assert_eq!(r.into_vec(), [0, 0]);
+/// assert_eq!(h.into_vec(), [Reverse(2)]);
+/// ```
+/// *equivalent to*
+/// ```
+/// # use std::collections::*;
+/// # use core::cmp::Reverse;
+/// let mut h = BinaryHeap::from(vec![Reverse(3), Reverse(1), Reverse(2)]);
+/// # assert_eq!(h.pop(), Some(Reverse(1)));
+/// ```
+/// ---
+/// keyed  
+/// builds a [KeyedHeap] that pops the element with the greatest key
+/// ```
+/// # use libsugar::*;
+/// let mut h = heap![by |s: &&str| s.len(); "aa", "a", "aaa"];
+/// h.push("aaaa");
+/// assert_eq!(h.pop(), Some("aaaa"));
+/// assert_eq!(h.peek(), Some(&"aaa"));
+/// ```
 #[macro_export]
 macro_rules! heap {
-    [ ] => { std::collections::BinaryHeap::new() };
-    [ $elem:expr; $n:expr ] => { std::collections::BinaryHeap::from(vec![$elem; $n]) };
-    [ $($e:expr),+ $(,)? ] => { std::collections::BinaryHeap::from(vec![$($e),+]) };
-}
\ No newline at end of file
+    [ @min; ] => { $crate::_alloc::BinaryHeap::new() };
+    [ @min; $($e:tt),+ $(,)? ] => {
+        $crate::_alloc::BinaryHeap::from($crate::_alloc::vec![$(::core::cmp::Reverse($e)),+])
+    };
+    [ @min; $($t:tt)* ] => {{
+        let mut v = $crate::_alloc::Vec::new();
+        $crate::_spread!(@push v push [] $($t)*);
+        ::core::iter::Iterator::collect::<$crate::_alloc::BinaryHeap<_>>(
+            ::core::iter::Iterator::map(::core::iter::IntoIterator::into_iter(v), ::core::cmp::Reverse)
+        )
+    }};
+    [ by $f:expr; ] => { $crate::KeyedHeap::new($f) };
+    [ by $f:expr; $($t:tt)* ] => {{
+        let mut v = $crate::_alloc::Vec::new();
+        $crate::_spread!(@push v push [] $($t)*);
+        $crate::KeyedHeap::from_vec(v, $f)
+    }};
+    [ ] => { $crate::_alloc::BinaryHeap::new() };
+    [ min; ] => { $crate::heap![@min;] };
+    [ min; .. $($t:tt)* ] => { $crate::heap![@min; .. $($t)*] };
+    [ $elem:expr; $n:expr ] => { $crate::_alloc::BinaryHeap::from($crate::_alloc::vec![$elem; $n]) };
+    [ min; $($t:tt)+ ] => { $crate::heap![@min; $($t)+] };
+    [ $($e:tt),+ $(,)? ] => { $crate::_alloc::BinaryHeap::from($crate::_alloc::vec![$($e),+]) };
+    [ $($t:tt)* ] => {{
+        let mut v = $crate::_alloc::Vec::new();
+        $crate::_spread!(@push v push [] $($t)*);
+        $crate::_alloc::BinaryHeap::from(v)
+    }};
+}
+
+/// build any `FromIterator` type  
+/// also takes `k => v` pairs, `..iterable` spreads and conditional entries like [map](macro.map.html)  
+/// the items are gathered into one array or `Vec` before `from_iter`, so they must all have the same type
+/// ```
+/// # use libsugar::*;
+/// # use std::path::PathBuf;
+/// # use std::collections::*;
+/// let s = collect![String; 'a', 'b', 'c'];
+/// assert_eq!(s, "abc");
+/// let p = collect![PathBuf; "usr", "lib"];
+/// assert_eq!(p, PathBuf::from("usr/lib"));
+/// let m = collect![BTreeMap<_, _>; 1 => 'a', 2 => 'b'];
+/// assert_eq!(m[&2], 'b');
+/// ```
+/// *equivalent to*
+/// ```
+/// # use std::iter::FromIterator;
+/// let s = String::from_iter(IntoIterator::into_iter(['a', 'b', 'c']));
+/// # assert_eq!(s, "abc");
+/// ```
+/// ---
+/// mixed item types need [extend](macro.extend.html), which handles each item on its own
+/// ```compile_fail
+/// # use libsugar::*;
+/// let b = String::from("b");
+/// let s = collect![String; "a", b.clone()];
+/// ```
+/// ```
+/// # use libsugar::*;
+/// let b = String::from("b");
+/// let mut s = String::new();
+/// extend!(s; "a", b.clone());
+/// # assert_eq!(s, "ab");
+/// ```
+#[macro_export]
+macro_rules! collect {
+    [ $t:ty; $($k:expr => $v:expr),+ $(,)? ] => {
+        <$t as ::core::iter::FromIterator<_>>::from_iter(::core::iter::IntoIterator::into_iter([$(($k, $v)),+]))
+    };
+    [ $t:ty; $($e:tt),* $(,)? ] => {
+        <$t as ::core::iter::FromIterator<_>>::from_iter(::core::iter::IntoIterator::into_iter([$($e),*]))
+    };
+    [ $t:ty; $($e:tt)* ] => {{
+        let mut v = $crate::_alloc::Vec::new();
+        $crate::extend!(v; $($e)*);
+        <$t as ::core::iter::FromIterator<_>>::from_iter(v)
+    }};
+}
+
+/// append items to any `Extend` type, reserving first when the target is a `Vec`, `VecDeque`, `String`, `HashMap` or `HashSet`  
+/// also takes `k => v` pairs, `..iterable` spreads and conditional entries like [map](macro.map.html)
+/// ```
+/// # use libsugar::*;
+/// # let quiet = false;
+/// let mut s = String::from("ab");
+/// extend!(s; 'c', "de", "!" if !quiet);
+/// assert_eq!(s, "abcde!");
+/// let mut m = map! { 1 => 'a' };
+/// extend!(m; 2 => 'b', ..vec![(3, 'c')]);
+/// assert_eq!(m.len(), 3);
+/// ```
+/// *equivalent to*
+/// ```
+/// # let quiet = false;
+/// let mut s = String::from("ab");
+/// s.reserve(2);
+/// s.extend(Some('c'));
+/// s.extend(Some("de"));
+/// if !quiet {
+///     s.extend(Some("!"));
+/// }
+/// # assert_eq!(s, "abcde!");
+/// ```
+#[macro_export]
+macro_rules! extend {
+    ( $c:expr; $($t:tt)* ) => {{
+        #[allow(unused_imports)]
+        use $crate::_extend::{_ExtendOne, _ExtendPair};
+        let mut c = &mut $c;
+        $crate::_extend!(@find c [$($t)*] $($t)*);
+    }};
+}
+
+/// Picks pairs when any entry is `k => v`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _extend {
+    (@find $c:ident [$($t:tt)*]) => { $crate::_spread!(@push $c _extend_one [] $($t)*) };
+    (@find $c:ident [$($t:tt)*] if $($rest:tt)*) => { $crate::_extend!(@find $c [$($t)*] $($rest)*) };
+    (@find $c:ident [$($t:tt)*] $k:expr => $($rest:tt)*) => { $crate::_spread!(@pair $c _extend_pair [] $($t)*) };
+    (@find $c:ident [$($t:tt)*] $e:expr $(, $($rest:tt)*)?) => { $crate::_extend!(@find $c [$($t)*] $($($rest)*)?) };
+    (@find $c:ident [$($t:tt)*] $x:tt $($rest:tt)*) => { $crate::_extend!(@find $c [$($t)*] $($rest)*) };
+}
+
+/// set algebra over `HashSet` or `BTreeSet` operands  
+/// `|` union, `&` intersection, `-` difference and `^` symmetric difference, with the precedence of the operators in Rust (`-` then `&` then `^` then `|`)  
+/// operands are borrowed, parenthesized groups nest and the result is a single new set of the operands' type
+/// ```
+/// # use libsugar::*;
+/// let a = set![1, 2, 3];
+/// let b = set![2, 3, 4];
+/// let c = set_ops!(a | b - set![3, 4]);
+/// assert_eq!(c, set![1, 2, 3]);
+/// let d = set_ops!((a | b) ^ set![1, 5]);
+/// assert_eq!(d, set![2, 3, 4, 5]);
+/// ```
+/// *equivalent to*
+/// ```
+/// # use std::collections::*;
+/// # let a: HashSet<_> = [1, 2, 3].iter().cloned().collect();
+/// # let b: HashSet<_> = [2, 3, 4].iter().cloned().collect();
+/// let t: HashSet<_> = b.difference(&[3, 4].iter().cloned().collect()).cloned().collect();
+/// let c: HashSet<_> = a.union(&t).cloned().collect();
+/// # assert_eq!(c, [1, 2, 3].iter().cloned().collect());
+/// ```
+#[macro_export]
+macro_rules! set_ops {
+    ( $($t:tt)+ ) => { $crate::_set_ops::eval($crate::_set_ops!(@ [] [] $($t)+)) };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _set_ops {
+    (@ [$($d:tt)*] [$($o:tt)+]) => { ($($d)* $crate::_set_ops!(@leaf $($o)+)) };
+    (@ [$($d:tt)*] [$($o:tt)+] | $($r:tt)+) => { $crate::_set_ops!(@ [$($d)* $crate::_set_ops!(@leaf $($o)+) |] [] $($r)+) };
+    (@ [$($d:tt)*] [$($o:tt)+] & $($r:tt)+) => { $crate::_set_ops!(@ [$($d)* $crate::_set_ops!(@leaf $($o)+) &] [] $($r)+) };
+    (@ [$($d:tt)*] [$($o:tt)+] - $($r:tt)+) => { $crate::_set_ops!(@ [$($d)* $crate::_set_ops!(@leaf $($o)+) -] [] $($r)+) };
+    (@ [$($d:tt)*] [$($o:tt)+] ^ $($r:tt)+) => { $crate::_set_ops!(@ [$($d)* $crate::_set_ops!(@leaf $($o)+) ^] [] $($r)+) };
+    (@ [$($d:tt)*] [$($o:tt)*] $t:tt $($r:tt)*) => { $crate::_set_ops!(@ [$($d)*] [$($o)* $t] $($r)*) };
+
+    (@leaf ( $($t:tt)+ )) => { $crate::_set_ops!(@ [] [] $($t)+) };
+    (@leaf $e:expr) => { $crate::_set_ops::Expr($crate::_set_ops::Leaf(&$e)) };
+}
+
+/// nested data literal, builds the collections of the other macros recursively  
+/// - `{ k: v, ... }` is a [map](macro.map.html)
+/// - `[a, b, ...]` is a `Vec`
+/// - `#{a, b, ...}` is a [set](macro.set.html)
+/// - `@kind` before one of them chooses the collection, maps take `map` `btmap`, sequences and sets take `vec` `deque` `list` `heap` `arr` `set` `btset`
+///
+/// keys are a single token tree, wrap longer keys in parentheses like `(-1): v`  
+/// values are expressions, wrap a block value in parentheses to keep it from being read as a map
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// # let port = 8080;
+/// let ports = lit!({
+///     "api": @btset #{port, port + 1},
+///     "admin": @btset #{9000},
+/// });
+/// # let _ = ports;
+/// ```
+/// *equivalent to*
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// # let port = 8080;
+/// let ports = map! {
+///     "api" => btset![port, port + 1],
+///     "admin" => btset![9000],
+/// };
+/// # let _ = ports;
+/// ```
+/// the types still have to agree like with the plain macros, so mixed values need a common type
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// let users: Vec<BTreeMap<&str, i32>> = lit!([
+///     @btmap { "id": 1, "age": 20 },
+///     @btmap { "id": 2, "age": -1 },
+/// ]);
+/// assert_eq!(users[1]["age"], -1);
+/// ```
+#[macro_export]
+macro_rules! lit {
+    ({ $($k:tt : $v:tt),* $(,)? }) => { $crate::_lit!(@emit_map map $(($k ($v)))*) };
+    ({ $($t:tt)* }) => { $crate::_lit!(@map map [] $($t)*) };
+    (# { $($e:tt),* $(,)? }) => { $crate::_lit!(@emit_seq set $(($e))*) };
+    (# { $($t:tt)* }) => { $crate::_lit!(@seq set [] $($t)*) };
+    ([ $($e:tt),* $(,)? ]) => { $crate::_lit!(@emit_seq vec $(($e))*) };
+    ([ $($t:tt)* ]) => { $crate::_lit!(@seq vec [] $($t)*) };
+    (@ $kind:ident { $($k:tt : $v:tt),* $(,)? }) => { $crate::_lit!(@emit_map $kind $(($k ($v)))*) };
+    (@ $kind:ident { $($t:tt)* }) => { $crate::_lit!(@map $kind [] $($t)*) };
+    (@ $kind:ident # { $($t:tt)* }) => { $crate::_lit!(@seq $kind [] $($t)*) };
+    (@ $kind:ident [ $($t:tt)* ]) => { $crate::_lit!(@seq $kind [] $($t)*) };
+    ($e:expr) => { $e };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _lit {
+    (@map $kind:ident [$($done:tt)*]) => { $crate::_lit!(@emit_map $kind $($done)*) };
+    (@map $kind:ident [$($done:tt)*] $k:tt : # $s:tt $(, $($rest:tt)*)?) => {
+        $crate::_lit!(@map $kind [$($done)* ($k (# $s))] $($($rest)*)?)
+    };
+    (@map $kind:ident [$($done:tt)*] $k:tt : @ $kd:ident # $s:tt $(, $($rest:tt)*)?) => {
+        $crate::_lit!(@map $kind [$($done)* ($k (@ $kd # $s))] $($($rest)*)?)
+    };
+    (@map $kind:ident [$($done:tt)*] $k:tt : @ $kd:ident $g:tt $(, $($rest:tt)*)?) => {
+        $crate::_lit!(@map $kind [$($done)* ($k (@ $kd $g))] $($($rest)*)?)
+    };
+    (@map $kind:ident [$($done:tt)*] $k:tt : $v:tt $(, $($rest:tt)*)?) => {
+        $crate::_lit!(@map $kind [$($done)* ($k ($v))] $($($rest)*)?)
+    };
+    (@map $kind:ident [$($done:tt)*] $k:tt : $v:expr $(, $($rest:tt)*)?) => {
+        $crate::_lit!(@map $kind [$($done)* ($k ($v))] $($($rest)*)?)
+    };
+
+    (@seq $kind:ident [$($done:tt)*]) => { $crate::_lit!(@emit_seq $kind $($done)*) };
+    (@seq $kind:ident [$($done:tt)*] # $s:tt $(, $($rest:tt)*)?) => {
+        $crate::_lit!(@seq $kind [$($done)* (# $s)] $($($rest)*)?)
+    };
+    (@seq $kind:ident [$($done:tt)*] @ $kd:ident # $s:tt $(, $($rest:tt)*)?) => {
+        $crate::_lit!(@seq $kind [$($done)* (@ $kd # $s)] $($($rest)*)?)
+    };
+    (@seq $kind:ident [$($done:tt)*] @ $kd:ident $g:tt $(, $($rest:tt)*)?) => {
+        $crate::_lit!(@seq $kind [$($done)* (@ $kd $g)] $($($rest)*)?)
+    };
+    (@seq $kind:ident [$($done:tt)*] $e:tt $(, $($rest:tt)*)?) => {
+        $crate::_lit!(@seq $kind [$($done)* ($e)] $($($rest)*)?)
+    };
+    (@seq $kind:ident [$($done:tt)*] $e:expr $(, $($rest:tt)*)?) => {
+        $crate::_lit!(@seq $kind [$($done)* ($e)] $($($rest)*)?)
+    };
+
+    (@emit_map map $(($k:tt ($($v:tt)*)))*) => { $crate::map!{ $($k => $crate::lit!($($v)*)),* } };
+    (@emit_map btmap $(($k:tt ($($v:tt)*)))*) => { $crate::btmap!{ $($k => $crate::lit!($($v)*)),* } };
+    (@emit_map $kind:ident $($t:tt)*) => {
+        ::core::compile_error!(::core::concat!("unknown map kind `", ::core::stringify!($kind), "`, expected `map` or `btmap`"))
+    };
+
+    (@emit_seq vec $(($($e:tt)*))*) => { $crate::_alloc::vec![$($crate::lit!($($e)*)),*] };
+    (@emit_seq deque $(($($e:tt)*))*) => { $crate::deque![$($crate::lit!($($e)*)),*] };
+    (@emit_seq list $(($($e:tt)*))*) => { $crate::list![$($crate::lit!($($e)*)),*] };
+    (@emit_seq heap $(($($e:tt)*))*) => { $crate::heap![$($crate::lit!($($e)*)),*] };
+    (@emit_seq arr $(($($e:tt)*))*) => { $crate::arr![$($crate::lit!($($e)*)),*] };
+    (@emit_seq set $(($($e:tt)*))*) => { $crate::set![$($crate::lit!($($e)*)),*] };
+    (@emit_seq btset $(($($e:tt)*))*) => { $crate::btset![$($crate::lit!($($e)*)),*] };
+    (@emit_seq $kind:ident $($t:tt)*) => {
+        ::core::compile_error!(::core::concat!(
+            "unknown collection kind `", ::core::stringify!($kind), "`, expected one of `vec` `deque` `list` `heap` `arr` `set` `btset`"
+        ))
+    };
+}
+
+/// `Vec` comprehension, clauses are `for pat in iter`, `if cond` and `let pat = expr`  
+/// the first clause must be a `for`, the outermost `for` borrows what it uses, a nested `for` moves the values it uses
+/// so outside collections read there should be referenced first (`let s = &s;`)
+/// ```
+/// # use libsugar::*;
+/// let xs = [1, 2, 3];
+/// let v = vec_comp![x * 2; for x in xs.iter(); if *x > 1];
+/// assert_eq!(v, [4, 6]);
+/// ```
+/// *equivalent to*
+/// ```
+/// let xs = [1, 2, 3];
+/// let v: Vec<_> = xs.iter().flat_map(|x| if *x > 1 { Some(x * 2) } else { None }).collect();
+/// assert_eq!(v, [4, 6]);
+/// ```
+/// ---
+/// nested
+/// ```
+/// # use libsugar::*;
+/// let pairs = vec_comp![(x, y); for x in 0..3; let y2 = x * 2; for y in x..y2];
+/// assert_eq!(pairs, [(1, 1), (2, 2), (2, 3)]);
+/// ```
+/// *equivalent to*
+/// ```
+/// let pairs: Vec<_> = (0..3).flat_map(|x| {
+///     let y2 = x * 2;
+///     (x..y2).map(move |y| (x, y))
+/// }).collect();
+/// assert_eq!(pairs, [(1, 1), (2, 2), (2, 3)]);
+/// ```
+/// ---
+/// extend an existing collection
+/// ```
+/// # use libsugar::*;
+/// let v = vec_comp![let vec![0]; x; for x in 1..3];
+/// assert_eq!(v, [0, 1, 2]);
+/// ```
+#[macro_export]
+macro_rules! vec_comp {
+    [ let $c:expr; $e:expr; $($t:tt)+ ] => { $crate::_comp!(@extend ($c) ($e) $($t)+) };
+    [ $e:expr; $($t:tt)+ ] => {
+        ::core::iter::Iterator::collect::<$crate::_alloc::Vec<_>>($crate::_comp!(@iter ($e) $($t)+))
+    };
+}
+
+/// `HashSet` comprehension, see [vec_comp](macro.vec_comp.html)
+/// ```
+/// # use libsugar::*;
+/// let s = set_comp![x % 3; for x in 0..10];
+/// assert_eq!(s.len(), 3);
+/// # use std::collections::*;
+/// let s = set_comp![let BTreeSet::new(); x % 3; for x in 0..10];
+/// assert_eq!(s.len(), 3);
+/// ```
+#[macro_export]
+macro_rules! set_comp {
+    [ let $c:expr; $e:expr; $($t:tt)+ ] => { $crate::_comp!(@extend ($c) ($e) $($t)+) };
+    [ $e:expr; $($t:tt)+ ] => { $crate::_comp!(@extend ($crate::_hash_new!(HashSet, new())) ($e) $($t)+) };
+}
+
+/// `HashMap` comprehension, see [vec_comp](macro.vec_comp.html)
+/// ```
+/// # use libsugar::*;
+/// let pairs = [("a", 1), ("b", 2)];
+/// let m = map_comp! { v => k; for (k, v) in pairs.iter().copied() };
+/// assert_eq!(m[&2], "b");
+/// # use std::collections::*;
+/// let m = map_comp! { let BTreeMap::new(); k => v * 10; for (k, v) in pairs.iter().copied(); if v > 1 };
+/// assert_eq!(m.len(), 1);
+/// ```
+#[macro_export]
+macro_rules! map_comp {
+    { let $c:expr; $k:expr => $v:expr; $($t:tt)+ } => { $crate::_comp!(@extend ($c) (($k, $v)) $($t)+) };
+    { $k:expr => $v:expr; $($t:tt)+ } => { $crate::_comp!(@extend ($crate::_hash_new!(HashMap, new())) (($k, $v)) $($t)+) };
+}
+
+/// comprehension into any `FromIterator`, see [vec_comp](macro.vec_comp.html)
+/// ```
+/// # use libsugar::*;
+/// # use std::collections::*;
+/// let q: VecDeque<_> = comp![c.to_ascii_uppercase(); for c in "abc".chars(); if c != 'b'];
+/// assert_eq!(q, ['A', 'C']);
+/// let s: String = comp![c; for w in ["ab", "cd"].iter(); for c in w.chars()];
+/// assert_eq!(s, "abcd");
+/// ```
+#[macro_export]
+macro_rules! comp {
+    [ let $c:expr; $e:expr; $($t:tt)+ ] => { $crate::_comp!(@extend ($c) ($e) $($t)+) };
+    [ $e:expr; $($t:tt)+ ] => { ::core::iter::Iterator::collect($crate::_comp!(@iter ($e) $($t)+)) };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _comp {
+    (@extend ($c:expr) ($e:expr) $($t:tt)+) => {{
+        let mut c = $c;
+        ::core::iter::Extend::extend(&mut c, $crate::_comp!(@iter ($e) $($t)+));
+        c
+    }};
+
+    (@iter ($e:expr) $($t:tt)+) => { $crate::_comp!(@for [] ($e) $($t)+) };
+    (@for [$($mv:tt)?] ($e:expr) for $p:pat in $it:expr) => {
+        ::core::iter::Iterator::map(::core::iter::IntoIterator::into_iter($it), $($mv)? |$p| $e)
+    };
+    (@for [$($mv:tt)?] ($e:expr) for $p:pat in $it:expr; $($rest:tt)+) => {
+        ::core::iter::Iterator::flat_map(::core::iter::IntoIterator::into_iter($it), $($mv)? |$p| $crate::_comp!(@rest ($e) $($rest)+))
+    };
+
+    (@rest ($e:expr)) => { ::core::option::Option::Some($e) };
+    (@rest ($e:expr) for $($rest:tt)+) => { $crate::_comp!(@for [move] ($e) for $($rest)+) };
+    (@rest ($e:expr) if $c:expr) => {
+        if $c { ::core::option::Option::Some($e) } else { ::core::option::Option::None }
+    };
+    (@rest ($e:expr) if $c:expr; $($rest:tt)+) => {
+        ::core::iter::Iterator::flatten(::core::iter::IntoIterator::into_iter(
+            if $c { ::core::option::Option::Some($crate::_comp!(@rest ($e) $($rest)+)) } else { ::core::option::Option::None }
+        ))
+    };
+    (@rest ($e:expr) let $p:pat = $v:expr $(; $($rest:tt)*)?) => {{
+        let $p = $v;
+        $crate::_comp!(@rest ($e) $($($rest)*)?)
+    }};
+}
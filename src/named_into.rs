@@ -39,6 +39,8 @@ named_into! { IntoRefCell;
     }
 }
 named_into! { IntoUnsafeCell;
+    /// # Safety
+    /// Access to the inner value must follow the rules of [`UnsafeCell`](core::cell::UnsafeCell)
     #[inline(always)]
     unsafe fn unsafe_cell(self) -> core::cell::UnsafeCell<Self> {
         core::cell::UnsafeCell::new(self)
@@ -115,6 +117,8 @@ pub trait IntoPin: Sized + core::ops::Deref {
     {
         core::pin::Pin::new(self)
     }
+    /// # Safety
+    /// See [`Pin::new_unchecked`](core::pin::Pin::new_unchecked)
     unsafe fn pin_unchecked(self) -> core::pin::Pin<Self> {
         core::pin::Pin::new_unchecked(self)
     }
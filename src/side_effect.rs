@@ -111,7 +111,7 @@ impl<T> AlsoMut for T {
     }
 }
 
-/// Run function immediately 
+/// Run function immediately
 #[inline(always)]
 pub fn run<R>(f: impl FnOnce() -> R) -> R {
     f()
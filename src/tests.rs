@@ -10,10 +10,87 @@ fn it_works() {
     assert_eq!(a, 3);
 }
 
+#[test]
+fn test_set_place() {
+    struct Counter {
+        count: i32,
+        total: i32,
+    }
+    impl Counter {
+        fn bump(&mut self) {
+            bop!(= self.count; + 1, * 2);
+            bop!(+= self.total; self.count, 1);
+        }
+        fn sum(&self) -> i32 {
+            self.count + self.total
+        }
+        fn fold(&mut self) {
+            bop!(+= self.total; self.sum(), self.total);
+        }
+    }
+    let mut c = Counter { count: 1, total: 0 };
+    c.bump();
+    assert_eq!(c.count, 4);
+    assert_eq!(c.total, 5);
+    c.fold();
+    assert_eq!(c.total, 19);
+
+    let mut v = vec![1, 2, 3];
+    let mut i = 0;
+    let mut next = || {
+        i += 1;
+        i
+    };
+    bop!(-= v[next()]; 1, 1);
+    assert_eq!(v, [1, 0, 3]);
+    assert_eq!(i, 1);
+    bop!(+= v[2]; v[0], v[2]);
+    assert_eq!(v, [1, 0, 7]);
+    let mut calls = 0;
+    let mut idx = || {
+        calls += 1;
+        1
+    };
+    bop!(= v[idx()]; + 2, * 3, - 1;!);
+    assert_eq!(v, [1, -5, 7]);
+    assert_eq!(calls, 1);
+    bop!(= v[0]; + v[0], * v[0]);
+    assert_eq!(v[0], 2);
+    let mut x = 1;
+    let mut s = Counter { count: 1, total: 0 };
+    bop!(= x; + x, * x);
+    bop!(= s.count; + s.count, * s.count);
+    assert_eq!(x, 2);
+    assert_eq!(x, s.count);
+
+    let mut s = String::from("a");
+    bop!(= s; + "b", + "c");
+    assert_eq!(s, "abc");
+
+    let mut x = 0b1100;
+    let p = &mut x;
+    bop!(= *p; | 1, >> 1);
+    bop!(<<= *p; 2);
+    assert_eq!(x, 0b11000);
+
+    #[derive(Debug, PartialEq)]
+    struct Only(i32);
+    impl core::ops::AddAssign<i32> for Only {
+        fn add_assign(&mut self, v: i32) {
+            self.0 += v;
+        }
+    }
+    let mut o = Only(1);
+    bop!(+= o; 2, 3);
+    assert_eq!(o, Only(6));
+}
+
 #[test]
 fn test_batch() {
     let x = bop!(|| 1 ; < ; 5, 6, 7, 0;!);
-    assert!(x)
+    assert!(x);
+    let y = bop!(&& 1 ; < ; 5, 6, 0);
+    assert!(!y);
 }
 
 #[test]
@@ -23,6 +100,35 @@ fn test_let() {
     assert_eq!(b, 2);
 }
 
+#[test]
+fn test_let_else() {
+    struct Point {
+        x: i32,
+        y: Option<i32>,
+    }
+    fn f(p: Point, s: &str) -> Result<i32, String> {
+        bop! {
+            let Point { x, y: Some(y) } = p,
+                Ok(mut n)|Result<i32, _> = s.parse(),
+                1..=9 = { n }
+            else {
+                return Err(format!("bad {}", s))
+            }
+        }
+        n += x + y;
+        Ok(n)
+    }
+    assert_eq!(f(Point { x: 1, y: Some(2) }, "3"), Ok(6));
+    assert_eq!(f(Point { x: 1, y: None }, "3"), Err("bad 3".to_string()));
+    assert_eq!(f(Point { x: 1, y: Some(2) }, "x"), Err("bad x".to_string()));
+    assert_eq!(f(Point { x: 1, y: Some(2) }, "0"), Err("bad 0".to_string()));
+
+    for v in [Some(1), None] {
+        bop! { let Some(v) = v else { continue } }
+        assert_eq!(v, 1);
+    }
+}
+
 #[allow(unused_variables)]
 #[test]
 fn test_match() {
@@ -66,11 +172,275 @@ fn test_match() {
     });
 }
 
+#[test]
+fn test_match_guard() {
+    let a = Some(5);
+    let b = "6";
+
+    let v: i32 = bop!(match && if b.len() == 1, Some(va) = a, if va > 3, Ok(vb) = b.parse::<i32>(), if vb > va => {
+        va + vb
+    } else {
+        0
+    });
+    assert_eq!(v, 11);
+
+    let v: i32 = bop!(!loop match && Some(va) = a, if va > 5, Ok(vb) = b.parse::<i32>() => {
+        va + vb
+    } else {
+        0
+    });
+    assert_eq!(v, 0);
+
+    let v: bool = bop!(bool match && Some(va) = a, if va < 3 => {});
+    assert!(!v);
+
+    let v: bool = bop!(!loop bool match && Some(va) = a, if va > 3 => {});
+    assert!(v);
+}
+
+#[test]
+fn test_match_fail() {
+    fn parse(header: Option<i32>, body: &str) -> Result<i32, String> {
+        bop!(match && "header": Some(h) = header, "body": Ok(b) = body.parse::<i32>(), if b > h => {
+            Ok(b - h)
+        } else |(i, what)| {
+            Err(format!("{} expected {}", i, what))
+        })
+    }
+    assert_eq!(parse(Some(1), "3"), Ok(2));
+    assert_eq!(parse(None, "3"), Err("0 expected header".to_string()));
+    assert_eq!(parse(Some(1), "x"), Err("1 expected body".to_string()));
+    let e = parse(Some(5), "3").unwrap_err();
+    assert!(e.starts_with("2 expected ") && e.contains('b') && e.contains('h'));
+
+    let a = Some(1);
+    let v: bool = bop!(bool match && Some(va) = a, if va > 1 => {} else |f| {
+        assert_eq!(f.0, 1);
+        assert!(f.1.contains("va"));
+    });
+    assert!(!v);
+
+    let v: &str = bop!(!loop match && Some(va) = a, Some(_) = Some(va) => {
+        "ok"
+    } else |(_, what)| {
+        what
+    });
+    assert_eq!(v, "ok");
+
+    let v: bool = bop!(!loop bool match && None = a => {} else |(i, what)| {
+        assert_eq!(i, 0);
+        assert_eq!(what, "None");
+    });
+    assert!(!v);
+}
+
+#[test]
+fn test_match_or() {
+    let a: Option<i32> = None;
+    let b: Result<i32, ()> = Ok(2);
+    let c = Some(3);
+
+    let v: i32 = bop!('a: match || Some(v) = a, Ok(v) = b, Some(v) = c => {
+        v
+    } else {
+        0
+    });
+    assert_eq!(v, 2);
+
+    let v: bool = bop!(bool 'b: match || Some(_) = a, Err(_) = b => {});
+    assert!(!v);
+
+    let v: i32 = bop!(!loop match || Some(v) = a, Some(v) = c => {
+        v
+    } else {
+        0
+    });
+    assert_eq!(v, 3);
+
+    let v: bool = bop!(!loop bool match || Some(_) = a, Some(_) = c => {});
+    assert!(v);
+}
+
+#[test]
+fn test_matches() {
+    #[derive(Debug)]
+    enum Token {
+        Num(i32),
+        Ident(String),
+        Comma,
+    }
+    use Token::*;
+
+    let t = Ident("let".to_string());
+    assert!(bop!(matches || t; Num(_), Ident(ref s) if s == "let"));
+    assert!(!bop!(matches || t; Num(_), Comma));
+    assert!(bop!(matches && t; Ident(_), Ident(ref s) if s.len() == 3));
+    assert!(!bop!(matches && t; Ident(_), Ident(ref s) if s.is_empty()));
+
+    let a = Num(1);
+    let b = Num(12);
+    assert!(bop!(matches && a, b; Num(n) if n > 0));
+    assert!(!bop!(matches && a, b, Comma; Num(_)));
+    assert!(bop!(matches || a, b, Comma; Comma));
+    assert!(!bop!(matches || a, b; Num(n) if n > 20));
+
+    let mut calls = 0;
+    let mut next = || {
+        calls += 1;
+        Some(calls)
+    };
+    assert!(bop!(matches && next(); Some(_), Some(1)));
+    assert_eq!(calls, 1);
+
+    let v = Some(2);
+    assert!(bop!(matches || v; Some(1) | Some(2), None));
+    assert!(!bop!(matches && v; Some(1) | Some(2), None | Some(3)));
+    assert!(bop!(matches && v; Some(1) | Some(2) if calls == 1, Some(n) if n > 1));
+    assert!(bop!(matches || a, b; Comma | Num(12)));
+    assert!(!bop!(matches && a, b; Num(1) | Comma));
+    assert!(bop!(matches && a, b; Num(1) | Num(12) if calls > 0));
+}
+
+#[test]
+fn test_fold() {
+    assert_eq!(bop!(+ 1, 2, 3), 6);
+    assert_eq!(bop!(*2, 3, 4), 24);
+    assert_eq!(bop!(min 3, 1, 2), 1);
+    assert_eq!(bop!(max 3, 1, 2), 3);
+    assert_eq!(bop!(min "b", "a", "c"), "a");
+    assert_eq!(bop!(max 7), 7);
+
+    fn gcd(a: u64, b: u64) -> u64 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+    assert_eq!(bop!(fold gcd; 12, 18, 27), 3);
+    assert_eq!(
+        bop!(fold |a: String, b: &str| a + b; String::new(), "a", "b"),
+        "ab"
+    );
+
+    let s = String::from("x");
+    let v = bop!(+ s, "y", "z");
+    assert_eq!(v, "xyz");
+
+    let x = 1;
+    let p = &x;
+    assert_eq!(bop!(*p), 1);
+
+    let nan = f64::NAN;
+    assert_eq!(bop!(partial_min nan, 2.0, 1.0), 1.0);
+    assert_eq!(bop!(partial_max 2.0, nan, 3.0), 3.0);
+    assert!(bop!(partial_min nan, nan).is_nan());
+    assert!(bop!(nan_min 1.0, nan, 0.0).is_nan());
+    assert!(bop!(nan_max nan, 1.0).is_nan());
+    assert_eq!(bop!(nan_max 1.0, 3.0, 2.0), 3.0);
+}
+
+#[test]
+fn test_cmp() {
+    let len = 3;
+    let cap = 4;
+    assert!(bop!(cmp 0 <= 1 < len <= cap));
+    assert!(!bop!(cmp 0 <= 3 < len <= cap));
+    assert!(bop!(cmp -1 < 0 == 0 != 1 >= 1 > 0));
+    assert!(bop!(cmp 0.5 < 1.0));
+
+    let mut calls = 0;
+    let mut f = |v: i32| {
+        calls += 1;
+        v
+    };
+    assert!(!bop!(cmp 2 < f(1) < f(3)));
+    assert_eq!(calls, 1);
+
+    let a = String::from("a");
+    let b = String::from("b");
+    assert!(bop!(cmp a < b >= (String::from("b"))));
+    assert!(bop!(cmp a <= a < b));
+    assert_eq!(a + &b, "ab");
+
+    struct Range {
+        lo: String,
+        hi: String,
+    }
+    impl Range {
+        fn contains(&self, s: &str) -> bool {
+            bop!(cmp *self.lo <= *s < *self.hi)
+        }
+        fn ordered(&self) -> bool {
+            bop!(cmp self.lo <= self.hi)
+        }
+    }
+    let r = Range {
+        lo: "b".into(),
+        hi: "d".into(),
+    };
+    assert!(r.contains("c"));
+    assert!(!r.contains("d"));
+    assert!(r.ordered());
+}
+
 #[test]
 fn test_in() {
     let r = 0..5;
     let c = bop!(&1, &2 => in && r);
     assert!(c);
+    let (min, max) = (&1, &4);
+    assert!(bop!(min, max => in && r));
+    assert!(!bop!(max => in || 0..2));
+}
+
+#[test]
+fn test_in_quantifiers() {
+    let r = 0..5;
+    let s = 3..10;
+    assert!(!bop!(&1, &7 => not in && r));
+    assert!(bop!(&7, &8 => not in && r));
+    assert!(bop!(&1, &7 => not in || r));
+    assert!(bop!(&7, &8 => none in r));
+    assert!(!bop!(&4 => none in r, s));
+    assert!(bop!(&20 => none in r, s));
+    assert_eq!(bop!(&1, &2, &7 => count in r), 2);
+    assert_eq!(bop!(&4 => count in r, s), 2);
+    assert!(bop!(&1, &2, &7 => at_least 2 in r));
+    assert!(!bop!(&1, &2, &7 => at_least 3 in r));
+    assert!(bop!(&7 => at_least 1 in r, s));
+
+    let empty: [bool; 2] = [bop!(=> none in r), bop!(&1 => none in)];
+    assert_eq!(empty, [true, true]);
+    assert_eq!(bop!(=> count in r), 0);
+    let empty: [bool; 4] = [
+        bop!(=> in && r),
+        bop!(=> not in && r),
+        bop!(&1 => in && ),
+        bop!(&1 => not in && ),
+    ];
+    assert_eq!(empty, [true; 4]);
+    let empty: [bool; 4] = [
+        bop!(=> in || r),
+        bop!(=> not in || r),
+        bop!(&1 => in || ),
+        bop!(&1 => not in || ),
+    ];
+    assert_eq!(empty, [false; 4]);
+    assert_eq!(bop!(&1 => count in), 0);
+    assert!(!bop!(=> at_least 1 in r));
+
+    struct Bag(Vec<i32>);
+    impl Bag {
+        fn has(&self, v: &i32) -> bool {
+            self.0.contains(v)
+        }
+    }
+    let bag = Bag(vec![1, 2, 3]);
+    assert!(bop!(has; &1, &2 => in && bag));
+    assert!(bop!(has; &4, &5 => none in bag));
+    assert_eq!(bop!(has; &1, &2, &4 => count in bag), 2);
+    assert!(bop!(has; &3, &4 => at_least 1 in bag));
 }
 
 #[allow(unused_variables)]
@@ -100,3 +470,416 @@ fn test_macro_set_set_like() {
 
     set![let BTreeSet::new(); 1, 2];
 }
+
+#[test]
+fn test_macro_map_capacity() {
+    use std::collections::{BTreeMap, HashMap, HashSet};
+
+    let m = map! { 1 => 2, 3 => 4, 5 => 6 };
+    assert!(m.capacity() >= 3);
+    let m = map! { let HashMap::new(); 1 => 2, 3 => 4, 5 => 6 };
+    assert!(m.capacity() >= 3);
+    let m = map! { let BTreeMap::new(); 1 => 2, 3 => 4 };
+    assert_eq!(m.len(), 2);
+    let s = set![let HashSet::new(); 1, 2, 3, 4];
+    assert!(s.capacity() >= 4);
+}
+
+#[test]
+fn test_macro_map_strict() {
+    use std::collections::BTreeSet;
+
+    let m = btmap! { strict; 1 => 2, 3 => 4 };
+    assert_eq!(m.len(), 2);
+    let s = set![strict let BTreeSet::new(); 1, 2];
+    assert_eq!(s.len(), 2);
+    assert_eq!(
+        try_set![let BTreeSet::new(); 1, 2, 2],
+        Err(DuplicateKey { key: 2, index: 2 })
+    );
+    let e = try_map! { "a" => 1, "a" => 2 }.unwrap_err();
+    assert_eq!(e.to_string(), "duplicate key \"a\" at entry 1");
+}
+
+#[test]
+#[should_panic(expected = "duplicate key 2 at entry 1")]
+fn test_macro_set_strict_panic() {
+    btset![strict; 2, 2];
+}
+
+#[test]
+fn test_macro_map_hasher() {
+    use std::collections::{HashMap, HashSet};
+
+    let a: HashMap<_, _, FnvBuildHasher> =
+        map! { hasher = FnvBuildHasher; 1 => 'a', 2 => 'b', 3 => 'c' };
+    let b: HashMap<_, _, FnvBuildHasher> =
+        map! { hasher = FnvBuildHasher; 3 => 'c', 1 => 'a', 2 => 'b' };
+    assert!(a.capacity() >= 3);
+    assert!(a.iter().eq(b.iter()));
+    let s: HashSet<_, FnvBuildHasher> = set![strict hasher = FnvBuildHasher; "x", "y"];
+    assert_eq!(s.len(), 2);
+    assert!(try_map! { hasher = FnvBuildHasher; 1 => 1, 1 => 2 }.is_err());
+}
+
+#[test]
+fn test_macro_lit() {
+    use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+    let m: HashMap<&str, Vec<BTreeMap<i32, i32>>> = lit!({
+        "a": [@btmap { 1: 2 }, @btmap {}],
+        "b": [],
+    });
+    assert_eq!(m["a"][0][&1], 2);
+    let s: BTreeSet<i32> = lit!(@btset #{ 1, 2 + 1, -3 });
+    assert!(s.contains(&-3));
+    let d: Vec<VecDeque<i32>> = lit!([@deque [1, 2], @deque [3 * 2]]);
+    assert_eq!(d[1][0], 6);
+    let n: BTreeMap<i32, BTreeSet<i32>> = lit!(@btmap { (-1): @btset #{ 1 }, 2: @btset #{} });
+    assert_eq!(n[&-1].len(), 1);
+}
+
+#[test]
+fn test_macro_spread() {
+    use std::collections::{BTreeMap, BTreeSet, HashMap, LinkedList, VecDeque};
+
+    let defaults = map! { "host" => "localhost", "port" => "80" };
+    let m = map! { ..defaults.clone(), "port" => "8080" };
+    assert_eq!(m["port"], "8080");
+    assert_eq!(m["host"], "localhost");
+    let m = map! { "port" => "8080", ..defaults.clone() };
+    assert_eq!(m["port"], "80");
+    let m: HashMap<_, _, FnvBuildHasher> =
+        map! { hasher = FnvBuildHasher; ..defaults, "user" => "root" };
+    assert_eq!(m.len(), 3);
+    let b: BTreeMap<_, _> = btmap! { 1 => 'a', ..vec![(2, 'b'), (1, 'c')] };
+    assert_eq!(b[&1], 'c');
+
+    let head = [1, 2];
+    let tail = vec![5, 6];
+    let a = arr![..head, 3, 2 + 2, ..tail.iter().copied()];
+    assert_eq!(&*a, &[1, 2, 3, 4, 5, 6]);
+    let d: VecDeque<_> = deque![0, ..head];
+    assert_eq!(d, [0, 1, 2]);
+    let l: LinkedList<_> = list![<- 0, ..head];
+    assert!(l.into_iter().eq(vec![2, 1, 0]));
+    let h = heap![..tail, 1];
+    assert_eq!(h.peek(), Some(&6));
+    let s: BTreeSet<_> = btset![..head, 2, 3];
+    assert_eq!(s.len(), 3);
+    let s = set![..head, ..head];
+    assert_eq!(s.len(), 2);
+}
+
+#[rustfmt::skip]
+#[test]
+fn test_macro_spread_large() {
+    use std::collections::BTreeSet;
+
+    let a = arr![
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+        25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49,
+        50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74,
+        75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99,
+        100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, 123, 124,
+        125, 126, 127, 128, 129, 130, 131, 132, 133, 134, 135, 136, 137, 138, 139, 140, 141, 142, 143, 144, 145, 146, 147, 148, 149,
+        150, 151, 152, 153, 154, 155, 156, 157, 158, 159, 160, 161, 162, 163, 164, 165, 166, 167, 168, 169, 170, 171, 172, 173, 174,
+        175, 176, 177, 178, 179, 180, 181, 182, 183, 184, 185, 186, 187, 188, 189, 190, 191, 192, 193, 194, 195, 196, 197, 198, 199,
+        200, 201, 202, 203, 204, 205, 206, 207, 208, 209, 210, 211, 212, 213, 214, 215, 216, 217, 218, 219, 220, 221, 222, 223, 224,
+        225, 226, 227, 228, 229, 230, 231, 232, 233, 234, 235, 236, 237, 238, 239, 240, 241, 242, 243, 244, 245, 246, 247, 248, 249,
+        ..vec![250, 251],
+    ];
+    assert_eq!(a.len(), 252);
+    assert_eq!(a[251], 251);
+    let a = arr![0, 1, 2, 3, 4, 5, 6, ..[7], 8, 9, 10, 11, 12, 13, 14, 15, 16, ..[17]];
+    assert!(a.iter().copied().eq(0..18));
+    let s: BTreeSet<_> = btset![
+        ..[-1],
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+        25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49,
+        50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74,
+        75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99,
+        100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, 123, 124,
+        125, 126, 127, 128, 129, 130, 131, 132, 133, 134, 135, 136, 137, 138, 139, 140, 141, 142, 143, 144, 145, 146, 147, 148, 149,
+        150, 151, 152, 153, 154, 155, 156, 157, 158, 159, 160, 161, 162, 163, 164, 165, 166, 167, 168, 169, 170, 171, 172, 173, 174,
+        175, 176, 177, 178, 179, 180, 181, 182, 183, 184, 185, 186, 187, 188, 189, 190, 191, 192, 193, 194, 195, 196, 197, 198, 199,
+        200, 201, 202, 203, 204, 205, 206, 207, 208, 209, 210, 211, 212, 213, 214, 215, 216, 217, 218, 219, 220, 221, 222, 223, 224,
+        225, 226, 227, 228, 229, 230, 231, 232, 233, 234, 235, 236, 237, 238, 239, 240, 241, 242, 243, 244, 245, 246, 247, 248, 249,
+        ..[-2],
+    ];
+    assert_eq!(s.len(), 252);
+    let m = map! {
+        0 => 0, 1 => 1, 2 => 2, 3 => 3, 4 => 4, 5 => 5, 6 => 6, 7 => 7, 8 => 8, 9 => 9, 10 => 10, 11 => 11, 12 => 12, 13 => 13, 14 => 14,
+        15 => 15, 16 => 16, 17 => 17, 18 => 18, 19 => 19, 20 => 20, 21 => 21, 22 => 22, 23 => 23, 24 => 24, 25 => 25, 26 => 26, 27 => 27, 28 => 28, 29 => 29,
+        30 => 30, 31 => 31, 32 => 32, 33 => 33, 34 => 34, 35 => 35, 36 => 36, 37 => 37, 38 => 38, 39 => 39, 40 => 40, 41 => 41, 42 => 42, 43 => 43, 44 => 44,
+        45 => 45, 46 => 46, 47 => 47, 48 => 48, 49 => 49, 50 => 50, 51 => 51, 52 => 52, 53 => 53, 54 => 54, 55 => 55, 56 => 56, 57 => 57, 58 => 58, 59 => 59,
+        60 => 60, 61 => 61, 62 => 62, 63 => 63, 64 => 64, 65 => 65, 66 => 66, 67 => 67, 68 => 68, 69 => 69, 70 => 70, 71 => 71, 72 => 72, 73 => 73, 74 => 74,
+        75 => 75, 76 => 76, 77 => 77, 78 => 78, 79 => 79, 80 => 80, 81 => 81, 82 => 82, 83 => 83, 84 => 84, 85 => 85, 86 => 86, 87 => 87, 88 => 88, 89 => 89,
+        90 => 90, 91 => 91, 92 => 92, 93 => 93, 94 => 94, 95 => 95, 96 => 96, 97 => 97, 98 => 98, 99 => 99, 100 => 100, 101 => 101, 102 => 102, 103 => 103, 104 => 104,
+        105 => 105, 106 => 106, 107 => 107, 108 => 108, 109 => 109, 110 => 110, 111 => 111, 112 => 112, 113 => 113, 114 => 114, 115 => 115, 116 => 116, 117 => 117, 118 => 118, 119 => 119,
+        120 => 120, 121 => 121, 122 => 122, 123 => 123, 124 => 124, 125 => 125, 126 => 126, 127 => 127, 128 => 128, 129 => 129, 130 => 130, 131 => 131, 132 => 132, 133 => 133, 134 => 134,
+        135 => 135, 136 => 136, 137 => 137, 138 => 138, 139 => 139, 140 => 140, 141 => 141, 142 => 142, 143 => 143, 144 => 144, 145 => 145, 146 => 146, 147 => 147, 148 => 148, 149 => 149,
+        150 => 150, 151 => 151, 152 => 152, 153 => 153, 154 => 154, 155 => 155, 156 => 156, 157 => 157, 158 => 158, 159 => 159, 160 => 160, 161 => 161, 162 => 162, 163 => 163, 164 => 164,
+        165 => 165, 166 => 166, 167 => 167, 168 => 168, 169 => 169, 170 => 170, 171 => 171, 172 => 172, 173 => 173, 174 => 174, 175 => 175, 176 => 176, 177 => 177, 178 => 178, 179 => 179,
+        180 => 180, 181 => 181, 182 => 182, 183 => 183, 184 => 184, 185 => 185, 186 => 186, 187 => 187, 188 => 188, 189 => 189, 190 => 190, 191 => 191, 192 => 192, 193 => 193, 194 => 194,
+        195 => 195, 196 => 196, 197 => 197, 198 => 198, 199 => 199, 200 => 200, 201 => 201, 202 => 202, 203 => 203, 204 => 204, 205 => 205, 206 => 206, 207 => 207, 208 => 208, 209 => 209,
+        ..vec![(210, 210)],
+    };
+    assert_eq!(m.len(), 211);
+    assert_eq!(m[&209], 209);
+}
+
+#[test]
+fn test_macro_conditional() {
+    use std::collections::{BTreeMap, LinkedList, VecDeque};
+
+    let on = true;
+    let none: Option<i32> = None;
+    let m0 = 0;
+    let m = map! { 1 => 1, 2 => 2 if !on, 3 => 3 + m0 if on, 4 =>? none, 5 =>? Some(5), ..vec![(6, 6)] };
+    assert_eq!(m.len(), 4);
+    assert!(!m.contains_key(&2));
+    let b: BTreeMap<_, _> = btmap! { 1 => 'a' if on, 2 =>? Some('b') };
+    assert_eq!(b.len(), 2);
+    let s = set![1, 2 if !on, 3 if on];
+    assert_eq!(s.len(), 2);
+    let d: VecDeque<_> = deque![1 if on, 2, 3 if !on, 4];
+    assert_eq!(d, [1, 2, 4]);
+    let l: LinkedList<_> = list![<- 1, 2 if on];
+    assert!(l.into_iter().eq(vec![2, 1]));
+    let a = arr![(1, 2) if on, (3, 4)];
+    assert_eq!(a.len(), 2);
+    let x = 10;
+    let m = map! { 1 => (x + if on { 1 } else { 2 }) if on, 2 => (if on { x } else { 0 }) if !on };
+    assert_eq!(m.len(), 1);
+    assert_eq!(m[&1], 11);
+}
+
+#[test]
+fn test_macro_conditional_large() {
+    let on = true;
+    let s = set![
+        -1 if on,
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+        25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49,
+        50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74,
+        75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99,
+        100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, 123, 124,
+        125, 126, 127, 128, 129, 130, 131, 132, 133, 134, 135, 136, 137, 138, 139, 140, 141, 142, 143, 144, 145, 146, 147, 148, 149,
+        150, 151, 152, 153, 154, 155, 156, 157, 158, 159, 160, 161, 162, 163, 164, 165, 166, 167, 168, 169, 170, 171, 172, 173, 174,
+        175, 176, 177, 178, 179, 180, 181, 182, 183, 184, 185, 186, 187, 188, 189, 190, 191, 192, 193, 194, 195, 196, 197, 198, 199,
+        200, 201, 202, 203, 204, 205, 206, 207, 208, 209, 210, 211, 212, 213, 214, 215, 216, 217, 218, 219, 220, 221, 222, 223, 224,
+        225, 226, 227, 228, 229, 230, 231, 232, 233, 234, 235, 236, 237, 238, 239, 240, 241, 242, 243, 244, 245, 246, 247, 248, 249,
+        -2 if !on,
+        1000
+            + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1
+            + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1
+            + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1
+            + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1
+            if on,
+    ];
+    assert_eq!(s.len(), 252);
+    assert!(s.contains(&1100) && !s.contains(&-2));
+    let m = map! {
+        0 => 0, 1 => 1, 2 => 2, 3 => 3, 4 => 4, 5 => 5, 6 => 6, 7 => 7, 8 => 8, 9 => 9, 10 => 10, 11 => 11, 12 => 12, 13 => 13, 14 => 14,
+        15 => 15, 16 => 16, 17 => 17, 18 => 18, 19 => 19, 20 => 20, 21 => 21, 22 => 22, 23 => 23, 24 => 24, 25 => 25, 26 => 26, 27 => 27, 28 => 28, 29 => 29,
+        30 => 30, 31 => 31, 32 => 32, 33 => 33, 34 => 34, 35 => 35, 36 => 36, 37 => 37, 38 => 38, 39 => 39, 40 => 40, 41 => 41, 42 => 42, 43 => 43, 44 => 44,
+        45 => 45, 46 => 46, 47 => 47, 48 => 48, 49 => 49, 50 => 50, 51 => 51, 52 => 52, 53 => 53, 54 => 54, 55 => 55, 56 => 56, 57 => 57, 58 => 58, 59 => 59,
+        60 => 60, 61 => 61, 62 => 62, 63 => 63, 64 => 64, 65 => 65, 66 => 66, 67 => 67, 68 => 68, 69 => 69, 70 => 70, 71 => 71, 72 => 72, 73 => 73, 74 => 74,
+        75 => 75, 76 => 76, 77 => 77, 78 => 78, 79 => 79, 80 => 80, 81 => 81, 82 => 82, 83 => 83, 84 => 84, 85 => 85, 86 => 86, 87 => 87, 88 => 88, 89 => 89,
+        90 => 90, 91 => 91, 92 => 92, 93 => 93, 94 => 94, 95 => 95, 96 => 96, 97 => 97, 98 => 98, 99 => 99, 100 => 100, 101 => 101, 102 => 102, 103 => 103, 104 => 104,
+        105 => 105, 106 => 106, 107 => 107, 108 => 108, 109 => 109, 110 => 110, 111 => 111, 112 => 112, 113 => 113, 114 => 114, 115 => 115, 116 => 116, 117 => 117, 118 => 118, 119 => 119,
+        120 => 120, 121 => 121, 122 => 122, 123 => 123, 124 => 124, 125 => 125, 126 => 126, 127 => 127, 128 => 128, 129 => 129, 130 => 130, 131 => 131, 132 => 132, 133 => 133, 134 => 134,
+        135 => 135, 136 => 136, 137 => 137, 138 => 138, 139 => 139, 140 => 140, 141 => 141, 142 => 142, 143 => 143, 144 => 144, 145 => 145, 146 => 146, 147 => 147, 148 => 148, 149 => 149,
+        150 => 150, 151 => 151, 152 => 152, 153 => 153, 154 => 154, 155 => 155, 156 => 156, 157 => 157, 158 => 158, 159 => 159, 160 => 160, 161 => 161, 162 => 162, 163 => 163, 164 => 164,
+        165 => 165, 166 => 166, 167 => 167, 168 => 168, 169 => 169, 170 => 170, 171 => 171, 172 => 172, 173 => 173, 174 => 174, 175 => 175, 176 => 176, 177 => 177, 178 => 178, 179 => 179,
+        180 => 180, 181 => 181, 182 => 182, 183 => 183, 184 => 184, 185 => 185, 186 => 186, 187 => 187, 188 => 188, 189 => 189, 190 => 190, 191 => 191, 192 => 192, 193 => 193, 194 => 194,
+        195 => 195, 196 => 196, 197 => 197, 198 => 198, 199 => 199, 200 => 200, 201 => 201, 202 => 202, 203 => 203, 204 => 204, 205 => 205, 206 => 206, 207 => 207, 208 => 208, 209 => 209,
+        -1 => if on { 1 } else { 2 } if on,
+        -2 => vec![1, 2].len() if !on,
+    };
+    assert_eq!(m.len(), 211);
+    assert_eq!(m[&-1], 1);
+}
+
+#[test]
+fn test_macro_comp() {
+    use std::collections::BTreeMap;
+
+    let grid = [vec![1, 2], vec![3], vec![]];
+    let flat = vec_comp![x; for row in grid.iter(); if !row.is_empty(); for x in row.iter().copied(); if x != 2];
+    assert_eq!(flat, [1, 3]);
+    let lens: BTreeMap<_, _> =
+        comp![(i, n); for (i, row) in grid.iter().enumerate(); let n = row.len(); if n > 0];
+    assert_eq!(lens.len(), 2);
+    let m = map_comp! { k => k * k; for k in 0..4; if k % 2 == 0 };
+    assert_eq!(m[&2], 4);
+    let s = set_comp![x / 2; for x in 0..6];
+    assert_eq!(s.len(), 3);
+    let v = comp![let vec![10]; x; for x in 0..2];
+    assert_eq!(v, [10, 0, 1]);
+
+    let allowed = set![1, 3];
+    let names = [String::from("a"), String::from("b")];
+    let picked = vec_comp![names[x % 2].clone(); for x in 0..5; if allowed.contains(&x)];
+    assert_eq!(picked, ["b", "b"]);
+    let inner = &allowed;
+    let pairs = vec_comp![(x, y); for x in 0..2; for y in 0..4; if inner.contains(&y)];
+    assert_eq!(pairs, [(0, 1), (0, 3), (1, 1), (1, 3)]);
+    assert_eq!(allowed.len(), 2);
+    assert_eq!(names.len(), 2);
+}
+
+#[test]
+fn test_macro_arr_fixed() {
+    let a = arr![vec![1]; 2];
+    assert_eq!(&*a, &[vec![1], vec![1]]);
+    let b: [Vec<usize>; 3] = arr![fn |i| vec![i; i]; 3];
+    assert_eq!(b[2], [2, 2]);
+    let c = arr![in (0..).map(|i| i.to_string()); 2];
+    assert_eq!(c, ["0", "1"]);
+    let d: Result<[String; 2], _> = arr![try in c.iter().cloned(); 2];
+    assert_eq!(d.unwrap(), c);
+    let e: Result<[String; 3], _> = arr![try in c; 3];
+    assert_eq!(e.unwrap_err().found, 2);
+}
+
+#[test]
+#[should_panic]
+fn test_macro_arr_fixed_short() {
+    let _: [i32; 3] = arr![in vec![1]; 3];
+}
+
+#[test]
+fn test_macro_heap_modes() {
+    use core::cmp::Reverse;
+
+    let mut h = heap![@min; 5, ..vec![2, 9], 4 if false];
+    assert_eq!(h.pop(), Some(Reverse(2)));
+    assert_eq!(h.into_sorted_vec(), [Reverse(9), Reverse(5)]);
+    let min = 7;
+    let r = heap![min; 3];
+    assert_eq!(r.into_vec(), [7, 7, 7]);
+    let mut h = heap![min; 5, ..vec![2, 9], 4 if false];
+    assert_eq!(h.pop(), Some(Reverse(2)));
+    let mut h = heap![min; ..vec![3, 1], 2];
+    assert_eq!(h.pop(), Some(Reverse(1)));
+    let h: std::collections::BinaryHeap<Reverse<i32>> = heap![min;];
+    assert!(h.is_empty());
+    let h = heap![@min; min];
+    assert_eq!(h.into_vec(), [Reverse(7)]);
+
+    let mut k = heap![by |p: &(&str, i32)| Reverse(p.1); ("b", 2), ("a", 1), ..vec![("c", 3)]];
+    assert_eq!(k.len(), 3);
+    assert_eq!(k.pop(), Some(("a", 1)));
+    k.extend(vec![("z", 0)]);
+    assert_eq!(k.peek(), Some(&("z", 0)));
+    assert_eq!(k.into_sorted_vec(), [("c", 3), ("b", 2), ("z", 0)]);
+}
+
+#[test]
+fn test_macro_multimap() {
+    use std::collections::{BTreeMap, VecDeque};
+
+    let routes = [("/", "index"), ("/a", "a")];
+    let m = multimap! { VecDeque; "/" => "home", ..routes.iter().copied(), "/b" => "b" if false };
+    assert_eq!(m["/"], ["home", "index"]);
+    assert_eq!(m.len(), 2);
+
+    let mut adj: BTreeMap<u8, Vec<u8>> = BTreeMap::new();
+    map_append! { multi adj; 1 => 2, 1 => 3 };
+    map_append! { multi adj; 1 => 4, 2 => 1 };
+    assert_eq!(adj[&1], [2, 3, 4]);
+    let _: &VecDeque<_> = &m["/a"];
+}
+
+#[test]
+fn test_macro_map_append_modes() {
+    let mut m = map! { 1 => vec![1] };
+    let old = map_append! { m; replaced; 1 => vec![2], 2 => vec![3], 2 => vec![4] };
+    assert_eq!(old, [(1, vec![1]), (2, vec![3])]);
+
+    map_append! { m; merge |mut a: Vec<_>, b| { a.extend(b); a }; 1 => vec![5], 3 => vec![6] };
+    assert_eq!(m[&1], [2, 5]);
+    assert_eq!(m[&3], [6]);
+
+    map_append! { m; keep; 3 => vec![], 4 => vec![7] };
+    assert_eq!(m[&3], [6]);
+    assert_eq!(m.len(), 4);
+}
+
+#[test]
+fn test_macro_set_ops() {
+    let a = btset![1, 2, 3, 4];
+    let b = btset![3, 4, 5];
+    let c = btset![4, 6];
+    assert_eq!(set_ops!(a & b), btset![3, 4]);
+    assert_eq!(set_ops!(a - b - c), btset![1, 2]);
+    assert_eq!(set_ops!(a ^ b | c), btset![1, 2, 4, 5, 6]);
+    assert_eq!(set_ops!(a ^ (b | c)), btset![1, 2, 5, 6]);
+    assert_eq!(set_ops!(a | b & c - btset![4]), a);
+
+    let names = set![String::from("a"), String::from("b")];
+    let r = set_ops!(names - set![String::from("a")]);
+    assert_eq!(r.len(), 1);
+    assert!(r.contains("b"));
+}
+
+#[test]
+fn test_macro_new_forms() {
+    use std::sync::Weak;
+
+    let rc = 1;
+    let a = new!(rc);
+    assert_eq!(*a, 1);
+    let b = new!(rc_refcell vec![1]);
+    b.borrow_mut().push(2);
+    assert_eq!(*b.borrow(), [1, 2]);
+    let c = new!(arc_mutex 0);
+    *c.lock().unwrap() += 1;
+    assert_eq!(*c.lock().unwrap(), 1);
+    let p = new!(pin String::from("p"));
+    assert_eq!(&*p, "p");
+
+    struct Node(Weak<Node>);
+    let n = new!(arc_cycle |w: &Weak<Node>| Node(w.clone()));
+    assert!(std::sync::Arc::ptr_eq(&n.0.upgrade().unwrap(), &n));
+
+    #[cfg(feature = "new-uninit")]
+    {
+        let u = Box::write(new!(uninit u8), 7);
+        assert_eq!(*u, 7);
+    }
+    #[cfg(feature = "new-zeroed")]
+    {
+        let z: Box<[u32; 64]> = unsafe { new!(zeroed [u32; 64]) };
+        assert!(z.iter().all(|x| *x == 0));
+    }
+}
+
+#[test]
+fn test_macro_collect_extend() {
+    use std::collections::{BTreeMap, LinkedList};
+
+    let flag = false;
+    let v = collect![LinkedList<_>; 1, 2 if flag, ..vec![3, 4], 5];
+    assert_eq!(v.into_iter().collect::<Vec<_>>(), [1, 3, 4, 5]);
+    let m = collect![BTreeMap<_, _>; ..vec![(1, 'a')], 2 => 'b', 3 => 'c' if flag];
+    assert_eq!(m.len(), 2);
+    let r = collect![Result<Vec<i32>, ()>; Ok(1), Err(()), Ok(2)];
+    assert!(r.is_err());
+    let b = String::from("b");
+    let s = collect![String; "a", &*b, ..["c"], "d" if !flag];
+    assert_eq!(s, "abcd");
+    let mut s = String::new();
+    extend!(s; "a", b.clone(), 'c');
+    assert_eq!(s, "abc");
+
+    struct Holder {
+        items: Vec<u8>,
+    }
+    let mut h = Holder { items: vec![] };
+    extend!(h.items; 1, 2, ..[3, 4].iter());
+    assert_eq!(h.items, [1, 2, 3, 4]);
+    assert!(h.items.capacity() >= 4);
+}
@@ -0,0 +1,8 @@
+//! Diagnostics of the `"proc-macro"` backend of `bop!`, one input per mode in `tests/ui`
+#![cfg(feature = "proc-macro")]
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
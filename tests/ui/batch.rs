@@ -0,0 +1,5 @@
+use libsugar::*;
+
+fn main() {
+    let _ = bop!(|| 1 ; < ; 5, , 6);
+}
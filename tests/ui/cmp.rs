@@ -0,0 +1,5 @@
+use libsugar::*;
+
+fn main() {
+    let _ = bop!(cmp 1 < 2 <);
+}
@@ -0,0 +1,7 @@
+use libsugar::*;
+
+fn main() {
+    let mut x = 1;
+    bop!(+= ; 1);
+    x += 1;
+}
@@ -0,0 +1,5 @@
+use libsugar::*;
+
+fn main() {
+    let _ = bop!(fold |a: i32, b| a + b 1, 2);
+}
@@ -0,0 +1,6 @@
+use libsugar::*;
+
+fn main() {
+    let r = 0..5;
+    let _ = bop!(&1, &2 => in && r, r);
+}
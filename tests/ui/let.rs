@@ -0,0 +1,7 @@
+use libsugar::*;
+
+fn main() {
+    let a: Option<i32> = None;
+    bop! { let b = }
+    let _ = a;
+}
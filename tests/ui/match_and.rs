@@ -0,0 +1,6 @@
+use libsugar::*;
+
+fn main() {
+    let a = Some(1);
+    let _: i32 = bop!(match && Some(v) = a => { v });
+}
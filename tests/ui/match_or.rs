@@ -0,0 +1,6 @@
+use libsugar::*;
+
+fn main() {
+    let a = Some(1);
+    let _ = bop!(bool match || Some(_) = a, if true => {});
+}
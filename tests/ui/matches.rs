@@ -0,0 +1,6 @@
+use libsugar::*;
+
+fn main() {
+    let (a, b) = (Some(1), Some(2));
+    let _ = bop!(matches || a, b; Some(_), None);
+}